@@ -0,0 +1,120 @@
+use super::{Precipitation, Temperature};
+use crate::{cmd::GenerateOptions, mesh::Mesh, topography::Topography};
+use ndarray::Array2;
+use serde::Deserialize;
+
+/// The biome of a hex, the community of plants and animals its climate can hold.
+///
+/// - `DeepOcean`: Water deeper than the continental shelves.
+/// - `Ocean`: Shallow water, over the shelves and around the coasts.
+/// - `Beach`: Low land touching the water.
+/// - `Ice`: Land frozen all year.
+/// - `Tundra`: Cold land with a short growing season, covered with mosses and shrubs.
+/// - `Taiga`: Cold land covered with conifer forests.
+/// - `TemperateForest`: Mild and wet land covered with broadleaf forests.
+/// - `Grassland`: Mild land too dry for forests.
+/// - `Desert`: Land with almost no rain.
+/// - `Savanna`: Hot land with a dry season, covered with grass and scattered trees.
+/// - `Rainforest`: Hot land with rain all year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Biome {
+    DeepOcean,
+    Ocean,
+    Beach,
+    Ice,
+    Tundra,
+    Taiga,
+    TemperateForest,
+    Grassland,
+    Desert,
+    Savanna,
+    Rainforest,
+}
+
+/// A row of the biome classification table.
+///
+/// A land hex matches the rule when it is colder than its temperature and drier than its
+/// precipitation. Missing bounds match every hex.
+///
+/// # Fields
+/// - `biome`: The biome given to the hexes matching the rule.
+/// - `temperature`: The mean annual temperature the hexes must be under, in degrees Celsius.
+/// - `precipitation`: The mean annual precipitation the hexes must be under, in millimeters.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BiomeRule {
+    pub biome: Biome,
+    pub temperature: Option<f32>,
+    pub precipitation: Option<f32>,
+}
+
+impl BiomeRule {
+    pub fn new(biome: Biome, temperature: Option<f32>, precipitation: Option<f32>) -> Self {
+        Self {
+            biome,
+            temperature,
+            precipitation,
+        }
+    }
+
+    fn matches(&self, temperature: f32, precipitation: f32) -> bool {
+        self.temperature.is_none_or(|bound| temperature < bound)
+            && self.precipitation.is_none_or(|bound| precipitation < bound)
+    }
+}
+
+/// The biome of each hex.
+pub type Biomes = Array2<Biome>;
+
+pub(super) trait BiomesBuilder {
+    fn build(
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        topography: &Topography,
+        temperature: &Temperature,
+        precipitation: &Precipitation,
+    ) -> Biomes;
+}
+
+impl BiomesBuilder for Biomes {
+    // Classify the hexes by their elevation, and the land by its climate
+    // The water is split by its depth, and the low land touching it is a beach. The rest of the
+    // land takes the biome of the first rule of the table it matches, or of the last rule when
+    // none does
+    fn build(
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        topography: &Topography,
+        temperature: &Temperature,
+        precipitation: &Precipitation,
+    ) -> Biomes {
+        let biomes = &options.biomes;
+        Array2::from_shape_fn(topography.elevations.dim(), |(x, y)| {
+            let elevation = topography.elevations[[x, y]];
+            if elevation <= biomes.depths {
+                return Biome::DeepOcean;
+            }
+            if elevation <= 0.0 {
+                return Biome::Ocean;
+            }
+            let coast = mesh
+                .get_hex(x as i32, y as i32)
+                .neighbors
+                .iter()
+                .any(|(n_coord, _wrapping)| !topography.is_land(n_coord.x, n_coord.y));
+            if coast && elevation <= biomes.beach {
+                return Biome::Beach;
+            }
+
+            let (temperature, precipitation) = (temperature[[x, y]], precipitation[[x, y]]);
+            biomes
+                .table
+                .iter()
+                .find(|rule| rule.matches(temperature, precipitation))
+                .or(biomes.table.last())
+                .expect("Error classifying biomes: the classification table is empty.")
+                .biome
+        })
+    }
+}
@@ -0,0 +1,192 @@
+use super::{latitude, Wind};
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{steps, unwrap, Coord, Mesh, Topology},
+    topography::Topography,
+};
+use hexx::Vec2;
+use ndarray::Array2;
+
+// Number of hexes under which a body of water is a sea too small to hold gyres
+const MIN_BASIN_AREA: usize = 64;
+
+// Distance, in hexes, over which the coasts turn the currents
+const COAST_REACH: f32 = 3.0;
+
+// Iterations of the averaging of the currents with their neighbors
+const SMOOTHING_STEPS: usize = 4;
+
+// Strength under which a current is too weak to carry its water far enough to be warm or cold
+const MIN_STRENGTH: f32 = 0.1;
+
+// Share of its strength a current must carry towards the poles or the equator to be warm or cold
+const MERIDIONAL_SHARE: f32 = 0.3;
+
+/// The temperature of the water carried by an ocean current.
+///
+/// - `Warm`: Water flowing from the equator towards the poles, warmer than the water around it.
+/// - `Cold`: Water flowing from the poles towards the equator, colder than the water around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Current {
+    Warm,
+    Cold,
+}
+
+/// The surface currents of the oceans.
+///
+/// The winds drag the water along the parallels, and the coasts turn it towards the poles or the
+/// equator, closing a gyre in each band of each ocean basin.
+///
+/// # Fields
+/// - `flow`: The direction of the current of each hex, scaled by its strength. It is zero on the
+///   land and on the seas too small to hold gyres.
+/// - `kind`: Whether the current of each water hex is warm or cold. It is `None` for the currents
+///   flowing along the parallels, and off the oceans.
+pub struct Currents {
+    pub flow: Array2<Vec2>,
+    pub kind: Array2<Option<Current>>,
+}
+
+impl Currents {
+    // Drag the water of the ocean basins along the parallels with the wind, and turn it along the
+    // coasts it runs into, towards the middle of the gyre of its latitude
+    // The gyres turn around the latitudes where the winds reverse, at 30 and 60 degrees, so the
+    // western edges of the oceans carry warm water towards the poles under the trade winds, and the
+    // eastern edges carry cold water back towards the equator under the westerlies
+    pub fn new(
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        topography: &Topography,
+        wind: &Wind,
+    ) -> Self {
+        let dim = topography.elevations.dim();
+        let oceans = oceans(mesh, topography);
+        let distances = coast_distance(mesh, &oceans);
+
+        let mut flow = Array2::from_shape_fn(dim, |(x, y)| {
+            let Some(distance) = distances[[x, y]] else {
+                return Vec2::ZERO;
+            };
+            let hex = mesh.get_hex(x as i32, y as i32);
+            let drift = Vec2::new(wind[[x, y]].x, 0.0);
+
+            // Towards the closest coast, down the distance to it
+            let shore = hex
+                .neighbors
+                .iter()
+                .filter(|(n_coord, _wrapping)| {
+                    distances[n_coord.to_dim()].is_none_or(|n_distance| n_distance < distance)
+                })
+                .map(|(n_coord, _wrapping)| {
+                    let center = mesh.get_hex(n_coord.x, n_coord.y).center;
+                    unwrap(center - hex.center, mesh.screen.period).normalize_or_zero()
+                })
+                .sum::<Vec2>()
+                .normalize_or_zero();
+            let blocked = drift.dot(shore);
+            if blocked <= 0.0 {
+                return drift;
+            }
+
+            let latitude = latitude(options, y as i32);
+            let center = match latitude.abs() < 45.0 {
+                true => 30.0,
+                false => 60.0,
+            };
+            // Along the y axis pointing south
+            let towards = match latitude.abs() < center {
+                true => -latitude.signum(),
+                false => latitude.signum(),
+            };
+            let mut along = shore.perp();
+            if along.y * towards < 0.0 {
+                along = -along;
+            }
+            let turn = (-(distance - 1.0) / COAST_REACH).exp();
+            drift + (along - shore) * blocked * turn
+        });
+
+        for _ in 0..SMOOTHING_STEPS {
+            flow = Array2::from_shape_fn(dim, |(x, y)| {
+                if distances[[x, y]].is_none() {
+                    return Vec2::ZERO;
+                }
+                let neighbors = &mesh.get_hex(x as i32, y as i32).neighbors;
+                let (sum, count) = neighbors
+                    .iter()
+                    .filter(|(n_coord, _wrapping)| distances[n_coord.to_dim()].is_some())
+                    .fold((flow[[x, y]], 1.0), |(sum, count), (n_coord, _wrapping)| {
+                        (sum + flow[n_coord.to_dim()], count + 1.0)
+                    });
+                sum / count
+            });
+        }
+
+        let kind = Array2::from_shape_fn(dim, |(x, y)| {
+            let current = flow[[x, y]];
+            let strength = current.length();
+            if strength < MIN_STRENGTH {
+                return None;
+            }
+            // Along the y axis pointing south
+            let poleward = -current.y * latitude(options, y as i32).signum();
+            if poleward > MERIDIONAL_SHARE * strength {
+                Some(Current::Warm)
+            } else if poleward < -MERIDIONAL_SHARE * strength {
+                Some(Current::Cold)
+            } else {
+                None
+            }
+        });
+
+        Currents { flow, kind }
+    }
+
+    /// The number of hexes crossed by a current of the given kind.
+    pub fn count(&self, current: Current) -> usize {
+        self.kind
+            .iter()
+            .filter(|kind| **kind == Some(current))
+            .count()
+    }
+}
+
+// Water hexes belonging to ocean basins large enough to hold gyres
+// The basins are the groups of water hexes connected to each other
+fn oceans(mesh: &Mesh, topography: &Topography) -> Array2<bool> {
+    let mut oceans = Array2::from_elem(topography.elevations.dim(), false);
+    let mut visited = Array2::from_elem(topography.elevations.dim(), false);
+    for ((x, y), elevation) in topography.elevations.indexed_iter() {
+        if visited[[x, y]] || *elevation > 0.0 {
+            continue;
+        }
+
+        let start = Coord::new(x as i32, y as i32);
+        let basin = steps(mesh, [start], u32::MAX, |coord| {
+            !topography.is_land(coord.x, coord.y)
+        });
+        basin.keys().for_each(|coord| visited[coord.to_dim()] = true);
+        if basin.len() >= MIN_BASIN_AREA {
+            basin.keys().for_each(|coord| oceans[coord.to_dim()] = true);
+        }
+    }
+    oceans
+}
+
+// Distance, in hexes, from each ocean hex to the closest hex out of the oceans
+// It is `None` out of the oceans, and `f32::MAX` for the ocean hexes of a map without coasts
+fn coast_distance(mesh: &Mesh, oceans: &Array2<bool>) -> Array2<Option<f32>> {
+    let mut distances = oceans.mapv(|ocean| ocean.then_some(f32::MAX));
+    let coasts = mesh.cells().into_iter().filter(|coord| {
+        oceans[coord.to_dim()]
+            && mesh
+                .neighbors(*coord)
+                .iter()
+                .any(|n_coord| !oceans[n_coord.to_dim()])
+    });
+    let reached = steps(mesh, coasts, u32::MAX, |coord| oceans[coord.to_dim()]);
+    for (coord, distance) in reached {
+        distances[coord.to_dim()] = Some(distance as f32 + 1.0);
+    }
+    distances
+}
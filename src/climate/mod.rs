@@ -0,0 +1,109 @@
+mod biomes;
+mod currents;
+mod precipitation;
+mod temperature;
+mod wind;
+
+pub use biomes::{Biome, BiomeRule, Biomes};
+use biomes::BiomesBuilder;
+pub use currents::{Current, Currents};
+pub use precipitation::Precipitation;
+use precipitation::PrecipitationBuilder;
+pub use temperature::Temperature;
+use temperature::TemperatureBuilder;
+pub use wind::{prevailing, Wind};
+use wind::WindBuilder;
+
+use crate::{cmd::GenerateOptions, mesh::Mesh, topography::Topography};
+use log::debug;
+
+/// The latitude of a row of the map, in degrees.
+///
+/// The equator lies at the share of the map height set in the options, the north pole at the top
+/// row and the south pole at the bottom one. When the equator is off the middle of the map, the
+/// latitudes of the shorter half are stretched so both poles stay at the edges.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::climate::latitude;
+/// use hextergen::cmd::GenerateOptions;
+///
+/// let options = GenerateOptions {
+///     height: 101,
+///     ..Default::default()
+/// };
+/// assert_eq!(latitude(&options, 0), 90.0);
+/// assert_eq!(latitude(&options, 50), 0.0);
+/// assert_eq!(latitude(&options, 100), -90.0);
+/// ```
+pub fn latitude(options: &GenerateOptions, y: i32) -> f32 {
+    let last = (options.height as f32 - 1.0).max(1.0);
+    let equator = options.climate.equator * last;
+    let row = y as f32;
+    if row < equator {
+        90.0 * (equator - row) / equator
+    } else if row > equator {
+        -90.0 * (row - equator) / (last - equator)
+    } else {
+        0.0
+    }
+}
+
+/// The climate of the map.
+///
+/// # Fields
+/// - `temperature`: The mean annual temperature of each hex, in degrees Celsius.
+/// - `wind`: The prevailing wind of each hex.
+/// - `currents`: The surface currents of the oceans.
+/// - `precipitation`: The mean annual precipitation of each hex, in millimeters.
+/// - `biomes`: The biome of each hex.
+pub struct Climate {
+    pub temperature: Temperature,
+    pub wind: Wind,
+    pub currents: Currents,
+    pub precipitation: Precipitation,
+    pub biomes: Biomes,
+}
+
+impl Climate {
+    pub fn new(options: &GenerateOptions, mesh: &Mesh, topography: &Topography) -> Self {
+        let wind = Wind::build(options);
+        let currents = Currents::new(options, mesh, topography, &wind);
+        debug!(
+            "Currents: {} warm hexes, {} cold hexes",
+            currents.count(Current::Warm),
+            currents.count(Current::Cold)
+        );
+        // The temperature and the precipitation share their array type, so their builders are
+        // named explicitly
+        let temperature =
+            <Temperature as TemperatureBuilder>::build(options, mesh, topography, &currents);
+        debug!(
+            "Temperature: {:.1} to {:.1}",
+            temperature.iter().copied().fold(f32::MAX, f32::min),
+            temperature.iter().copied().fold(f32::MIN, f32::max)
+        );
+        let precipitation = <Precipitation as PrecipitationBuilder>::build(
+            mesh,
+            topography,
+            &temperature,
+            &wind,
+            &currents,
+        );
+        debug!(
+            "Precipitation: {:.0} to {:.0}",
+            precipitation.iter().copied().fold(f32::MAX, f32::min),
+            precipitation.iter().copied().fold(f32::MIN, f32::max)
+        );
+        let biomes = Biomes::build(options, mesh, topography, &temperature, &precipitation);
+
+        Self {
+            temperature,
+            wind,
+            currents,
+            precipitation,
+            biomes,
+        }
+    }
+}
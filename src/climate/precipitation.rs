@@ -0,0 +1,134 @@
+use super::{Current, Currents, Temperature, Wind};
+use crate::{
+    mesh::{unwrap, Coord, Mesh},
+    topography::Topography,
+};
+use ndarray::Array2;
+
+// Moisture picked up by the air over each water hex, as a share of what it can hold
+const EVAPORATION: f32 = 0.3;
+
+// Share of the evaporation added over the warm currents, and removed over the cold ones
+const CURRENT_EVAPORATION: f32 = 0.25;
+
+// Share of its moisture the air drops on each hex it crosses, over water and over land
+const OCEAN_RAIN: f32 = 0.05;
+const LAND_RAIN: f32 = 0.08;
+
+// Share of its moisture the air drops per unit of normalized elevation it climbs
+const OROGRAPHIC_RAIN: f32 = 4.0;
+
+// Rise of the moisture the air can hold per degree, from the Clausius-Clapeyron relation
+const HUMIDITY_GROWTH: f32 = 0.07;
+
+// Precipitation, in millimeters per year, of the rain dropped by air holding its capacity at 0°
+const RAIN_SCALE: f32 = 6000.0;
+
+// Weight of every neighbor in the spread of the air, besides the ones downwind, so the air
+// converging between two wind bands is mixed instead of piling up on a single row
+const DIFFUSION: f32 = 0.3;
+
+// Iterations of the moisture transport, bounding the number of hexes the moisture travels
+const TRANSPORT_STEPS: usize = 96;
+
+/// The mean annual precipitation of each hex, in millimeters.
+pub type Precipitation = Array2<f32>;
+
+pub(super) trait PrecipitationBuilder {
+    fn build(
+        mesh: &Mesh,
+        topography: &Topography,
+        temperature: &Temperature,
+        wind: &Wind,
+        currents: &Currents,
+    ) -> Precipitation;
+}
+
+impl PrecipitationBuilder for Precipitation {
+    // Carry the moisture downwind until it settles
+    // On each step, the air of every hex moves to its downwind neighbors. Over water it picks up
+    // moisture, up to what it can hold at the temperature of the hex. It drops a share of its
+    // moisture on every hex, the excess over what it can hold when it cools, and more when it
+    // climbs the land, so the windward slopes are wet and the land behind the mountains is dry
+    // The warm currents feed the air with more moisture, while the cold ones leave the coasts
+    // along them dry
+    fn build(
+        mesh: &Mesh,
+        topography: &Topography,
+        temperature: &Temperature,
+        wind: &Wind,
+        currents: &Currents,
+    ) -> Precipitation {
+        let elevations = &topography.elevations;
+        let downwind = downwind(mesh, wind);
+        let capacity = temperature.mapv(|t| (HUMIDITY_GROWTH * t).exp());
+        let evaporation = currents.kind.mapv(|kind| match kind {
+            Some(Current::Warm) => EVAPORATION * (1.0 + CURRENT_EVAPORATION),
+            Some(Current::Cold) => EVAPORATION * (1.0 - CURRENT_EVAPORATION),
+            None => EVAPORATION,
+        });
+
+        let mut outgoing = Array2::<f32>::zeros(elevations.dim());
+        let mut precipitation = Array2::<f32>::zeros(elevations.dim());
+        for _ in 0..TRANSPORT_STEPS {
+            let mut incoming = Array2::<f32>::zeros(elevations.dim());
+            let mut orographic = Array2::<f32>::zeros(elevations.dim());
+            for ((x, y), targets) in downwind.indexed_iter() {
+                for (n_coord, weight) in targets {
+                    let amount = outgoing[[x, y]] * weight;
+                    let rise = (elevations[n_coord.to_dim()].max(0.0)
+                        - elevations[[x, y]].max(0.0))
+                    .max(0.0);
+                    let rain = amount * (OROGRAPHIC_RAIN * rise).min(1.0);
+                    incoming[n_coord.to_dim()] += amount - rain;
+                    orographic[n_coord.to_dim()] += rain;
+                }
+            }
+
+            for ((x, y), moisture) in incoming.indexed_iter() {
+                let capacity = capacity[[x, y]];
+                let (mut moisture, share) = match topography.is_land(x as i32, y as i32) {
+                    true => (*moisture, LAND_RAIN),
+                    false => (
+                        (moisture + evaporation[[x, y]] * capacity).min(capacity),
+                        OCEAN_RAIN,
+                    ),
+                };
+                let excess = (moisture - capacity).max(0.0);
+                moisture -= excess;
+                let rain = moisture * share;
+                precipitation[[x, y]] = orographic[[x, y]] + excess + rain;
+                outgoing[[x, y]] = moisture - rain;
+            }
+        }
+
+        precipitation * RAIN_SCALE
+    }
+}
+
+// Neighbors the air of each hex moves to, with the share of the air moved to each one
+// The air is split among the neighbors in the direction of the wind, favoring the ones closest
+// to it, and a little among all of them
+fn downwind(mesh: &Mesh, wind: &Wind) -> Array2<Vec<(Coord, f32)>> {
+    Array2::from_shape_fn(wind.dim(), |(x, y)| {
+        let hex = mesh.get_hex(x as i32, y as i32);
+        let direction = wind[[x, y]].normalize_or_zero();
+        let alignments: Vec<(Coord, f32)> = hex
+            .neighbors
+            .iter()
+            .map(|(n_coord, _wrapping)| {
+                let center = mesh.get_hex(n_coord.x, n_coord.y).center;
+                let offset = unwrap(center - hex.center, mesh.screen.period);
+                (
+                    *n_coord,
+                    offset.normalize_or_zero().dot(direction).max(0.0).powi(2) + DIFFUSION,
+                )
+            })
+            .collect();
+        let total: f32 = alignments.iter().map(|(_, alignment)| alignment).sum();
+        alignments
+            .into_iter()
+            .map(|(n_coord, alignment)| (n_coord, alignment / total))
+            .collect()
+    })
+}
@@ -0,0 +1,130 @@
+use super::{latitude, Current, Currents};
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{nearest, Coord, Mesh},
+    topography::Topography,
+};
+use ndarray::Array2;
+
+// Sea level temperature, in degrees, reached with no insolation, and gained per unit of it
+// Chosen so an Earth-like tilt gives about 27 degrees at the equator, -3 at 60 degrees of latitude
+// and -13 at the poles, accounting for the heat the atmosphere carries towards the poles
+const BASE_TEMPERATURE: f32 = -42.3;
+const INSOLATION_TEMPERATURE: f32 = 56.0;
+
+// Altitude, in kilometers, of the highest land hex
+const MAX_ALTITUDE: f32 = 8.0;
+
+// Distance, in hexes, over which the influence of the ocean fades inland
+const MODERATION_REACH: f32 = 6.0;
+
+// Iterations of the spread of the heat of the currents to the water around them
+const CURRENT_SPREAD: usize = 3;
+
+/// The mean annual temperature of each hex, in degrees Celsius.
+pub type Temperature = Array2<f32>;
+
+pub(super) trait TemperatureBuilder {
+    fn build(
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        topography: &Topography,
+        currents: &Currents,
+    ) -> Temperature;
+}
+
+impl TemperatureBuilder for Temperature {
+    // Warm each hex by the mean insolation of its latitude, cool the land by the lapse rate, and
+    // pull the hexes close to the ocean towards the mean temperature of the sea level
+    // The warm and cold currents then shift the temperature of the water they cross, and of the
+    // land along it
+    fn build(
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        topography: &Topography,
+        currents: &Currents,
+    ) -> Temperature {
+        let climate = &options.climate;
+        let dim = topography.elevations.dim();
+        let sea_level = Array2::from_shape_fn(dim, |(_x, y)| {
+            BASE_TEMPERATURE
+                + INSOLATION_TEMPERATURE * insolation(latitude(options, y as i32), climate.tilt)
+        });
+        let mean = sea_level.mean().unwrap_or(0.0);
+        let distances = ocean_distance(mesh, topography);
+        let currents = current_temperature(options, mesh, topography, currents);
+
+        Array2::from_shape_fn(dim, |(x, y)| {
+            let elevation = topography.elevations[[x, y]];
+            let altitude = elevation.max(0.0) * MAX_ALTITUDE;
+            let temperature = sea_level[[x, y]] - climate.lapse_rate * altitude;
+
+            let Some((distance, water)) = distances[[x, y]] else {
+                return temperature;
+            };
+            let reach = (-distance / MODERATION_REACH).exp();
+            let moderation = (mean - temperature) * climate.moderation;
+            temperature + (moderation + currents[water.to_dim()]) * reach
+        })
+    }
+}
+
+// Temperature the currents add to each water hex
+// The warm and cold currents shift the temperature of the water they cross, and mix with the
+// water around them, so the temperature doesn't jump at their edges
+fn current_temperature(
+    options: &GenerateOptions,
+    mesh: &Mesh,
+    topography: &Topography,
+    currents: &Currents,
+) -> Array2<f32> {
+    let mut temperature = currents.kind.mapv(|kind| match kind {
+        Some(Current::Warm) => options.climate.currents,
+        Some(Current::Cold) => -options.climate.currents,
+        None => 0.0,
+    });
+    for _ in 0..CURRENT_SPREAD {
+        temperature = Array2::from_shape_fn(temperature.dim(), |(x, y)| {
+            if topography.is_land(x as i32, y as i32) {
+                return 0.0;
+            }
+            let neighbors = &mesh.get_hex(x as i32, y as i32).neighbors;
+            let (sum, count) = neighbors
+                .iter()
+                .filter(|(n_coord, _wrapping)| !topography.is_land(n_coord.x, n_coord.y))
+                .fold(
+                    (temperature[[x, y]], 1.0),
+                    |(sum, count), (n_coord, _wrapping)| {
+                        (sum + temperature[n_coord.to_dim()], count + 1.0)
+                    },
+                );
+            sum / count
+        });
+    }
+    temperature
+}
+
+// Mean annual insolation at a latitude, relative to the mean of the planet
+// Second order approximation of the insolation averaged over the year, which flattens as the
+// axial tilt grows, until the poles receive more than the equator beyond 54 degrees of tilt
+fn insolation(latitude: f32, tilt: f32) -> f32 {
+    let legendre = |x: f32| (3.0 * x * x - 1.0) / 2.0;
+    let coefficient = -5.0 / 8.0 * legendre(tilt.to_radians().cos());
+    1.0 + coefficient * legendre(latitude.to_radians().sin())
+}
+
+// Distance, in hexes, from each hex to the closest water hex, along with that hex
+// It is `None` everywhere when the map has no water
+fn ocean_distance(mesh: &Mesh, topography: &Topography) -> Array2<Option<(f32, Coord)>> {
+    let mut distances = Array2::from_elem(topography.elevations.dim(), None);
+    let water = topography
+        .elevations
+        .indexed_iter()
+        .filter(|(_, elevation)| **elevation <= 0.0)
+        .map(|((x, y), _)| Coord::new(x as i32, y as i32))
+        .map(|coord| (coord, coord));
+    for (coord, (distance, water)) in nearest(mesh, water, u32::MAX, |_| true) {
+        distances[coord.to_dim()] = Some((distance as f32, water));
+    }
+    distances
+}
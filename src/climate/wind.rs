@@ -0,0 +1,51 @@
+use super::latitude;
+use crate::cmd::GenerateOptions;
+use hexx::Vec2;
+use ndarray::Array2;
+
+/// The prevailing wind of each hex, as a direction on the map scaled by its strength.
+///
+/// The map y axis points south, so a wind blowing north has a negative y.
+pub type Wind = Array2<Vec2>;
+
+pub(super) trait WindBuilder {
+    fn build(options: &GenerateOptions) -> Wind;
+}
+
+impl WindBuilder for Wind {
+    // Blow the prevailing wind of its latitude over each hex
+    fn build(options: &GenerateOptions) -> Wind {
+        Array2::from_shape_fn(
+            (options.width as usize, options.height as usize),
+            |(_x, y)| prevailing(latitude(options, y as i32)),
+        )
+    }
+}
+
+/// The prevailing wind at a latitude, in degrees.
+///
+/// The circulation is split in three bands on each hemisphere, each one strongest in its middle
+/// and calm at its edges:
+/// - Trade winds, up to 30°: blowing west and towards the equator.
+/// - Westerlies, from 30° to 60°: blowing east and towards the pole.
+/// - Polar easterlies, beyond 60°: blowing west and towards the equator.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::climate::prevailing;
+///
+/// // Northern trade winds blow south west
+/// let trades = prevailing(15.0);
+/// assert!(trades.x < 0.0 && trades.y > 0.0);
+///
+/// // Southern westerlies blow south east
+/// let westerlies = prevailing(-45.0);
+/// assert!(westerlies.x > 0.0 && westerlies.y > 0.0);
+/// ```
+pub fn prevailing(latitude: f32) -> Vec2 {
+    let strength = (6.0 * latitude.abs()).to_radians().sin();
+    // Towards the equator, along the y axis pointing south
+    let equatorward = latitude.signum();
+    Vec2::new(-1.0, 0.5 * equatorward).normalize() * strength
+}
@@ -1,6 +1,12 @@
 use argh::FromArgs;
 use hexx::{orientation, HexOrientation, OffsetHexMode};
+use crate::climate::{Biome, BiomeRule};
 use crate::generate;
+use crate::mesh::Coord;
+use crate::topography::seeds;
+use crate::utils::noise::{BillowNoise, Noise, OctaveNoise, RidgedNoise, WarpedNoise, WorleyNoise};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::{Path, PathBuf};
 
 const TITLE: &str = r"
 
@@ -60,14 +66,901 @@ struct Generate {
         description = "orientation of the hexes in the map (flat or pointy)"
     )]
     orientation: Option<String>,
+
+    #[argh(
+        option,
+        long = "wrapping",
+        description = "axes the map wraps around (flat, cylinder or torus)"
+    )]
+    wrapping: Option<String>,
+
+    #[argh(
+        option,
+        short = 'c',
+        long = "config",
+        description = "TOML file with the generation options, overridden by the other flags"
+    )]
+    config: Option<PathBuf>,
+
+    #[argh(option, long = "plates", description = "number of tectonic plates")]
+    plates: Option<u32>,
+
+    #[argh(
+        option,
+        long = "plate-spacing",
+        description = "minimum distance in hexes between plate seeds"
+    )]
+    plate_spacing: Option<f32>,
+
+    #[argh(
+        option,
+        long = "seed-attempts",
+        description = "rejected seed candidates allowed before relaxing the spacing"
+    )]
+    seed_attempts: Option<u32>,
+
+    #[argh(
+        option,
+        long = "seed-strategy",
+        description = "plate seed placement strategy (random, poisson or jittered)"
+    )]
+    seed_strategy: Option<String>,
+
+    #[argh(
+        option,
+        long = "seeds-file",
+        description = "file with one x,y plate seed per line, overrides the seed strategy"
+    )]
+    seeds_file: Option<PathBuf>,
+
+    #[argh(
+        option,
+        long = "noise-preset",
+        description = "elevation noise preset (archipelago, pangea or continents)"
+    )]
+    noise_preset: Option<String>,
+
+    #[argh(
+        option,
+        long = "noise-kind",
+        description = "elevation noise family (fbm, ridged, billow, worley or warped)"
+    )]
+    noise_kind: Option<String>,
+
+    #[argh(
+        option,
+        long = "frequency",
+        description = "frequency of the first elevation noise octave"
+    )]
+    frequency: Option<f32>,
+
+    #[argh(
+        option,
+        long = "amplitude",
+        description = "amplitude of the first elevation noise octave"
+    )]
+    amplitude: Option<f32>,
+
+    #[argh(
+        option,
+        long = "octaves",
+        description = "number of elevation noise octaves"
+    )]
+    octaves: Option<u32>,
+
+    #[argh(
+        option,
+        long = "persistence",
+        description = "amplitude multiplier between elevation noise octaves"
+    )]
+    persistence: Option<f32>,
+
+    #[argh(
+        option,
+        long = "lacunarity",
+        description = "frequency multiplier between elevation noise octaves"
+    )]
+    lacunarity: Option<f32>,
+
+    #[argh(
+        option,
+        long = "warp",
+        description = "coordinate displacement of the warped elevation noise"
+    )]
+    warp: Option<f32>,
+
+    #[argh(
+        option,
+        long = "land-ratio",
+        description = "share of the map above the sea level, between 0 and 1"
+    )]
+    land_ratio: Option<f32>,
+
+    #[argh(
+        option,
+        long = "drift-steps",
+        description = "number of plate drift steps simulated before the interactions"
+    )]
+    drift_steps: Option<u32>,
+
+    #[argh(
+        option,
+        long = "drift-step-size",
+        description = "time covered by each plate drift step"
+    )]
+    drift_step_size: Option<f32>,
+
+    #[argh(option, long = "hotspots", description = "number of mantle hotspots")]
+    hotspots: Option<u32>,
+
+    #[argh(
+        option,
+        long = "hotspot-trail",
+        description = "number of extinct volcanoes left behind by each hotspot"
+    )]
+    hotspot_trail: Option<u32>,
+
+    #[argh(
+        option,
+        long = "erosion-droplets",
+        description = "number of rain droplets simulated by the hydraulic erosion"
+    )]
+    erosion_droplets: Option<u32>,
+
+    #[argh(
+        option,
+        long = "erosion-rate",
+        description = "share of the free capacity a droplet erodes from each hex, between 0 and 1"
+    )]
+    erosion_rate: Option<f32>,
+
+    #[argh(
+        option,
+        long = "deposition-rate",
+        description = "share of the excess sediment a droplet deposits on each hex, between 0 and 1"
+    )]
+    deposition_rate: Option<f32>,
+
+    #[argh(
+        option,
+        long = "thermal-iterations",
+        description = "number of thermal erosion iterations"
+    )]
+    thermal_iterations: Option<u32>,
+
+    #[argh(
+        option,
+        long = "talus",
+        description = "steepest elevation difference kept between neighbors by the thermal erosion"
+    )]
+    talus: Option<f32>,
+
+    #[argh(
+        option,
+        long = "river-threshold",
+        description = "number of hexes a hex must drain to hold a river"
+    )]
+    river_threshold: Option<u32>,
+
+    #[argh(
+        option,
+        long = "evaporation",
+        description = "water evaporated from each lake hex, in millimeters per year"
+    )]
+    evaporation: Option<f32>,
+
+    #[argh(
+        option,
+        long = "layer",
+        description = "layer drawn in the rendered images (debug, basins, temperature, precipitation, biomes, currents or regions)"
+    )]
+    layer: Option<String>,
+
+    #[argh(
+        option,
+        long = "tilt",
+        description = "axial tilt of the planet in degrees, between 0 and 90"
+    )]
+    tilt: Option<f32>,
+
+    #[argh(
+        option,
+        long = "equator",
+        description = "position of the equator as a share of the map height, between 0 and 1"
+    )]
+    equator: Option<f32>,
+
+    #[argh(
+        option,
+        long = "lapse-rate",
+        description = "temperature lost per kilometer of altitude, in degrees"
+    )]
+    lapse_rate: Option<f32>,
+
+    #[argh(
+        option,
+        long = "ocean-moderation",
+        description = "share of the temperature extremes removed near the ocean, between 0 and 1"
+    )]
+    ocean_moderation: Option<f32>,
+
+    #[argh(
+        option,
+        long = "current-temperature",
+        description = "temperature added by warm ocean currents and removed by cold ones, in degrees"
+    )]
+    current_temperature: Option<f32>,
+}
+
+/// Strategy used to place the seeds of the tectonic plates.
+///
+/// - `Random`: Random hexes kept at a minimum distance from each other.
+/// - `Poisson`: Bridson's Poisson disk sampling over the mesh.
+/// - `Jittered`: One seed at a random position inside each cell of a grid.
+/// - `List`: A user supplied list of seeds.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedPlacement {
+    #[default]
+    Random,
+    Poisson,
+    Jittered,
+    List(Vec<Coord>),
+}
+
+/// Options controlling the climate of the map.
+///
+/// # Fields
+/// - `tilt`: The axial tilt of the planet, in degrees between 0 and 90. The larger the tilt, the
+///   smaller the difference of temperature between the equator and the poles.
+/// - `equator`: The position of the equator, as a share of the map height from the top.
+/// - `lapse_rate`: The temperature lost per kilometer of altitude above the sea level, in degrees.
+/// - `moderation`: The share of their difference with the mean temperature the ocean removes from
+///   the hexes close to it, between 0 and 1.
+/// - `currents`: The temperature the warm ocean currents add to the water they cross, and the cold
+///   ones remove from it, in degrees.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClimateOptions {
+    pub tilt: f32,
+    pub equator: f32,
+    pub lapse_rate: f32,
+    pub moderation: f32,
+    pub currents: f32,
+}
+
+impl Default for ClimateOptions {
+    fn default() -> Self {
+        Self {
+            tilt: 23.44,
+            equator: 0.5,
+            lapse_rate: 6.5,
+            moderation: 0.3,
+            currents: 4.0,
+        }
+    }
+}
+
+/// Options controlling the classification of the hexes into biomes.
+///
+/// In a configuration file, the table is a list of `[[biomes.table]]` rules, replacing the default
+/// one.
+///
+/// # Fields
+/// - `depths`: The elevation under which the water is deep ocean, between -1 and 0.
+/// - `beach`: The elevation under which the land touching the water is a beach, between 0 and 1.
+/// - `table`: The rules classifying the rest of the land by its climate, checked in order. The
+///   hexes matching no rule take the biome of the last one.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BiomesOptions {
+    pub depths: f32,
+    pub beach: f32,
+    pub table: Vec<BiomeRule>,
+}
+
+impl Default for BiomesOptions {
+    fn default() -> Self {
+        Self {
+            depths: -0.3,
+            beach: 0.01,
+            table: vec![
+                BiomeRule::new(Biome::Ice, Some(-10.0), None),
+                BiomeRule::new(Biome::Tundra, Some(-3.0), None),
+                BiomeRule::new(Biome::Desert, None, Some(250.0)),
+                BiomeRule::new(Biome::Taiga, Some(5.0), None),
+                BiomeRule::new(Biome::Grassland, Some(20.0), Some(750.0)),
+                BiomeRule::new(Biome::TemperateForest, Some(20.0), None),
+                BiomeRule::new(Biome::Savanna, None, Some(1500.0)),
+                BiomeRule::new(Biome::Rainforest, None, None),
+            ],
+        }
+    }
 }
 
-#[derive(Debug)]
+/// Options controlling the ice covering the map.
+///
+/// # Fields
+/// - `sheet`: The mean annual temperature under which the land is buried under an ice sheet, in
+///   degrees.
+/// - `pack`: The mean annual temperature under which the sea is frozen all year, in degrees.
+/// - `seasonal`: The mean annual temperature under which the sea freezes in winter, in degrees. It
+///   can't be under the pack ice temperature.
+/// - `thickness`: The elevation of the top of the thickest ice sheets, between 0 and 1. The land
+///   under an ice sheet is raised to its surface.
+/// - `flattening`: The share of the height of the land standing out of an ice sheet ground down by
+///   the glaciers, between 0 and 1.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CryosphereOptions {
+    pub sheet: f32,
+    pub pack: f32,
+    pub seasonal: f32,
+    pub thickness: f32,
+    pub flattening: f32,
+}
+
+impl Default for CryosphereOptions {
+    fn default() -> Self {
+        Self {
+            sheet: -10.0,
+            pack: -6.0,
+            seasonal: 0.0,
+            thickness: 0.3,
+            flattening: 0.5,
+        }
+    }
+}
+
+/// Options controlling the classification of the land and water regions.
+///
+/// # Fields
+/// - `continent`: The share of the map a landmass must cover to be a continent, between 0 and 1.
+/// - `ocean`: The share of the map a body of water must cover to be an ocean, between 0 and 1.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegionsOptions {
+    pub continent: f32,
+    pub ocean: f32,
+}
+
+impl Default for RegionsOptions {
+    fn default() -> Self {
+        Self {
+            continent: 0.01,
+            ocean: 0.01,
+        }
+    }
+}
+
+/// Axes the map wraps around, walking off one edge of the map leading back in from the opposite
+/// edge.
+///
+/// - `Flat`: The map wraps around no axis, like a continent with edges on every side.
+/// - `Cylinder`: The map wraps around the x axis, like a globe whose poles are the top and bottom
+///   edges.
+/// - `Torus`: The map wraps around both the x and y axes, and has no edges at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Wrapping {
+    Flat,
+    #[default]
+    Cylinder,
+    Torus,
+}
+
+impl Wrapping {
+    /// Whether the map wraps around the x axis.
+    pub fn wraps_x(&self) -> bool {
+        matches!(self, Wrapping::Cylinder | Wrapping::Torus)
+    }
+
+    /// Whether the map wraps around the y axis.
+    pub fn wraps_y(&self) -> bool {
+        matches!(self, Wrapping::Torus)
+    }
+}
+
+/// Layer drawn in the rendered images.
+///
+/// - `Debug`: The elevations, with the plate interactions, the terrain features and the water.
+/// - `Basins`: The drainage basins, each one in its own color.
+/// - `Temperature`: The mean annual temperature, from blue for the coldest hexes to red for the
+///   hottest ones.
+/// - `Precipitation`: The mean annual precipitation, from sand for the driest hexes to blue for
+///   the wettest ones.
+/// - `Biomes`: The biome of each hex.
+/// - `Currents`: The ocean currents, warm ones in red and cold ones in blue, with their direction.
+/// - `Regions`: The continents, islands, oceans and inland seas, each one in its own color.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    #[default]
+    Debug,
+    Basins,
+    Temperature,
+    Precipitation,
+    Biomes,
+    Currents,
+    Regions,
+}
+
+/// Options controlling the placement of the tectonic plates.
+///
+/// # Fields
+/// - `placement`: The strategy used to place the plate seeds.
+/// - `count`: The number of plates to generate. Ignored when the seeds are supplied as a list.
+/// - `spacing`: The minimum distance, in hexes, between two plate seeds. When `None` it is derived
+///   from the map diagonal and the plate count.
+/// - `attempts`: The number of consecutive rejected seed candidates allowed before the spacing is
+///   relaxed.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlatesOptions {
+    pub placement: SeedPlacement,
+    pub count: u32,
+    pub spacing: Option<f32>,
+    pub attempts: u32,
+}
+
+impl Default for PlatesOptions {
+    fn default() -> Self {
+        Self {
+            placement: SeedPlacement::default(),
+            count: 75,
+            spacing: None,
+            attempts: 1000,
+        }
+    }
+}
+
+/// Named combinations of noise options for the base elevation.
+///
+/// - `Archipelago`: High frequency noise, breaking the land into many small islands.
+/// - `Pangea`: Low frequency noise, merging the land into a few large masses.
+/// - `Continents`: The default balance between both.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoisePreset {
+    Archipelago,
+    Pangea,
+    Continents,
+}
+
+impl NoisePreset {
+    pub fn options(&self) -> NoiseOptions {
+        match self {
+            NoisePreset::Archipelago => NoiseOptions {
+                frequency: 12.0,
+                octaves: 16,
+                persistence: 0.55,
+                lacunarity: 2.2,
+                ..Default::default()
+            },
+            NoisePreset::Pangea => NoiseOptions {
+                frequency: 2.0,
+                persistence: 0.5,
+                ..Default::default()
+            },
+            NoisePreset::Continents => NoiseOptions::default(),
+        }
+    }
+}
+
+/// Family of the noise used by a generation stage.
+///
+/// - `Fbm`: Fractal brownian motion, smooth and natural looking.
+/// - `Ridged`: Ridged multifractal, with sharp ridges.
+/// - `Billow`: Rounded lumps separated by creases.
+/// - `Worley`: Cellular domes separated by valleys.
+/// - `Warped`: Fractal brownian motion with its coordinates displaced by the warp noise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoiseKind {
+    #[default]
+    Fbm,
+    Ridged,
+    Billow,
+    Worley,
+    Warped,
+}
+
+/// Options controlling the octave noise of a generation stage.
+///
+/// # Fields
+/// - `kind`: The noise family.
+/// - `frequency`: The frequency of the first octave.
+/// - `amplitude`: The amplitude of the first octave.
+/// - `octaves`: The number of octaves.
+/// - `persistence`: The amplitude multiplier applied on each octave.
+/// - `lacunarity`: The frequency multiplier applied on each octave.
+/// - `warp`: The maximum coordinate displacement of the `Warped` family.
+#[derive(Debug, Clone)]
+pub struct NoiseOptions {
+    pub kind: NoiseKind,
+    pub frequency: f32,
+    pub amplitude: f32,
+    pub octaves: u32,
+    pub persistence: f32,
+    pub lacunarity: f32,
+    pub warp: f32,
+}
+
+impl Default for NoiseOptions {
+    fn default() -> Self {
+        Self {
+            kind: NoiseKind::Fbm,
+            frequency: 5.0,
+            amplitude: 1.0,
+            octaves: 20,
+            persistence: 0.6,
+            lacunarity: 2.0,
+            warp: 0.1,
+        }
+    }
+}
+
+impl NoiseOptions {
+    /// Builds the noise generator described by the options.
+    ///
+    /// The `Warped` family displaces its coordinates with the `warp` noise.
+    pub fn build(&self, seed: u64, warp: &NoiseOptions) -> Box<dyn Noise> {
+        let source = self.family(seed);
+        match self.kind {
+            NoiseKind::Warped => Box::new(WarpedNoise::new(
+                source,
+                warp.family(seed.wrapping_add(1)),
+                self.warp,
+            )),
+            _ => source,
+        }
+    }
+
+    // Noise of the family, the `Warped` one sampling the fractal brownian motion it displaces
+    fn family(&self, seed: u64) -> Box<dyn Noise> {
+        let (frequency, amplitude, octaves, persistence, lacunarity) = (
+            self.frequency,
+            self.amplitude,
+            self.octaves,
+            self.persistence,
+            self.lacunarity,
+        );
+        match self.kind {
+            NoiseKind::Fbm | NoiseKind::Warped => Box::new(OctaveNoise::new(
+                seed,
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            )),
+            NoiseKind::Ridged => Box::new(RidgedNoise::new(
+                seed,
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            )),
+            NoiseKind::Billow => Box::new(BillowNoise::new(
+                seed,
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            )),
+            NoiseKind::Worley => Box::new(WorleyNoise::new(
+                seed,
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            )),
+        }
+    }
+}
+
+/// Options controlling the noise sampled by each generation stage.
+///
+/// In a configuration file, each stage has its own table, like `[noise.elevation]`. A `preset`
+/// set in a table replaces the defaults of the stage, and the remaining keys override its values.
+///
+/// # Fields
+/// - `elevation`: The noise of the base elevation.
+/// - `mountains`: The noise scaling the uplift along the convergent plate borders, so the
+///   mountain ranges rise into peaks and dip into passes along their length.
+/// - `warp`: The noise displacing the coordinates of the stages using the `Warped` family. It
+///   can't be of the `Warped` family itself.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "NoiseStagesConfig")]
+pub struct NoiseStagesOptions {
+    pub elevation: NoiseOptions,
+    pub mountains: NoiseOptions,
+    pub warp: NoiseOptions,
+}
+
+impl Default for NoiseStagesOptions {
+    fn default() -> Self {
+        Self {
+            elevation: NoiseOptions::default(),
+            mountains: NoiseOptions {
+                kind: NoiseKind::Ridged,
+                frequency: 8.0,
+                octaves: 6,
+                persistence: 0.5,
+                ..Default::default()
+            },
+            // A smoother, lower frequency noise for the displacement
+            warp: NoiseOptions {
+                frequency: 2.5,
+                octaves: 4,
+                persistence: 0.5,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+// `[noise]` table of a configuration file, with a table for each stage
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoiseStagesConfig {
+    elevation: Option<NoiseConfig>,
+    mountains: Option<NoiseConfig>,
+    warp: Option<NoiseConfig>,
+}
+
+impl From<NoiseStagesConfig> for NoiseStagesOptions {
+    fn from(config: NoiseStagesConfig) -> Self {
+        let defaults = NoiseStagesOptions::default();
+        Self {
+            elevation: NoiseConfig::apply(config.elevation, defaults.elevation),
+            mountains: NoiseConfig::apply(config.mountains, defaults.mountains),
+            warp: NoiseConfig::apply(config.warp, defaults.warp),
+        }
+    }
+}
+
+// Table of a stage in the `[noise]` table of a configuration file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoiseConfig {
+    preset: Option<NoisePreset>,
+    kind: Option<NoiseKind>,
+    frequency: Option<f32>,
+    amplitude: Option<f32>,
+    octaves: Option<u32>,
+    persistence: Option<f32>,
+    lacunarity: Option<f32>,
+    warp: Option<f32>,
+}
+
+impl NoiseConfig {
+    // Override the defaults of a stage, or the preset when the table sets one, with the keys of
+    // the table
+    fn apply(config: Option<NoiseConfig>, defaults: NoiseOptions) -> NoiseOptions {
+        let Some(config) = config else {
+            return defaults;
+        };
+        let preset = config.preset.map_or(defaults, |p| p.options());
+        NoiseOptions {
+            kind: config.kind.unwrap_or(preset.kind),
+            frequency: config.frequency.unwrap_or(preset.frequency),
+            amplitude: config.amplitude.unwrap_or(preset.amplitude),
+            octaves: config.octaves.unwrap_or(preset.octaves),
+            persistence: config.persistence.unwrap_or(preset.persistence),
+            lacunarity: config.lacunarity.unwrap_or(preset.lacunarity),
+            warp: config.warp.unwrap_or(preset.warp),
+        }
+    }
+}
+
+/// Options controlling the drift of the tectonic plates before their interactions are applied.
+///
+/// # Fields
+/// - `steps`: The number of simulated steps. No drift is simulated when it is 0.
+/// - `step_size`: The time covered by each step, scaling the distance the plates move.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DriftOptions {
+    pub steps: u32,
+    pub step_size: f32,
+}
+
+impl Default for DriftOptions {
+    fn default() -> Self {
+        Self {
+            steps: 0,
+            step_size: 1.0,
+        }
+    }
+}
+
+/// Options controlling the hotspots, mantle plumes raising volcanoes inside the plates.
+///
+/// # Fields
+/// - `count`: The number of hotspots.
+/// - `trail`: The maximum number of extinct volcanoes left behind by each hotspot along the
+///   motion of its plate.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HotspotsOptions {
+    pub count: u32,
+    pub trail: u32,
+}
+
+impl Default for HotspotsOptions {
+    fn default() -> Self {
+        Self { count: 6, trail: 8 }
+    }
+}
+
+/// Options controlling the detection of the peaks and the mountain ranges.
+///
+/// # Fields
+/// - `prominence`: The lowest prominence of a peak, its height above the highest saddle leading
+///   to a higher summit.
+/// - `highlands`: The share of the highest land hexes which can belong to a mountain range,
+///   between 0 and 1.
+/// - `reach`: The farthest distance, in hexes, of the hexes of a mountain range from a convergent
+///   plate border.
+/// - `size`: The smallest number of hexes of a mountain range.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MountainsOptions {
+    pub prominence: f32,
+    pub highlands: f32,
+    pub reach: u32,
+    pub size: u32,
+}
+
+impl Default for MountainsOptions {
+    fn default() -> Self {
+        Self {
+            prominence: 0.02,
+            highlands: 0.15,
+            reach: 6,
+            size: 5,
+        }
+    }
+}
+
+/// Options controlling the erosion of the terrain.
+///
+/// # Fields
+/// - `droplets`: The number of rain droplets simulated by the hydraulic erosion. No hydraulic
+///   erosion is applied when it is 0.
+/// - `rate`: The share of its free capacity a droplet erodes from each hex, between 0 and 1.
+/// - `deposition`: The share of its excess sediment a droplet deposits on each hex, between 0
+///   and 1.
+/// - `thermal_iterations`: The number of thermal erosion iterations. No thermal erosion is
+///   applied when it is 0.
+/// - `talus`: The steepest normalized elevation difference the thermal erosion keeps between two
+///   neighbor hexes.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErosionOptions {
+    pub droplets: u32,
+    pub rate: f32,
+    pub deposition: f32,
+    pub thermal_iterations: u32,
+    pub talus: f32,
+}
+
+impl Default for ErosionOptions {
+    fn default() -> Self {
+        Self {
+            droplets: 50000,
+            rate: 0.1,
+            deposition: 0.3,
+            thermal_iterations: 10,
+            talus: 0.03,
+        }
+    }
+}
+
+/// Options controlling the shape of the sea floor.
+///
+/// # Fields
+/// - `shelf`: The width of the continental shelves around the coasts, in hexes.
+/// - `shelf_depth`: The depth of the water at the outer edge of the continental shelves, between 0
+///   and 1.
+/// - `slope`: The width of the continental slopes, from the edge of the shelves down to the
+///   abyssal plains, in hexes.
+/// - `abyss_depth`: The depth of the abyssal plains, between the shelf depth and 1.
+/// - `relief`: The share of the relief of the sea floor kept over the shelves, slopes and plains,
+///   between 0 and 1.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BathymetryOptions {
+    pub shelf: u32,
+    pub shelf_depth: f32,
+    pub slope: u32,
+    pub abyss_depth: f32,
+    pub relief: f32,
+}
+
+impl Default for BathymetryOptions {
+    fn default() -> Self {
+        Self {
+            shelf: 3,
+            shelf_depth: 0.05,
+            slope: 3,
+            abyss_depth: 0.5,
+            relief: 0.3,
+        }
+    }
+}
+
+/// Options controlling the water flowing over the land.
+///
+/// # Fields
+/// - `river_threshold`: The number of hexes draining through a hex for it to hold a river.
+/// - `evaporation`: The water evaporated from each lake hex, in millimeters per year. Lakes
+///   evaporating more than the rain falling on their catchment become endorheic salt flats.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HydrologyOptions {
+    pub river_threshold: u32,
+    pub evaporation: f32,
+}
+
+impl Default for HydrologyOptions {
+    fn default() -> Self {
+        Self {
+            river_threshold: 20,
+            evaporation: 250.0,
+        }
+    }
+}
+
+/// Options controlling the generation of a map.
+///
+/// # Fields
+/// - `seed`: The seed from which every random process is derived.
+/// - `width`: The width of the map, in hexes.
+/// - `height`: The height of the map, in hexes.
+/// - `orientation`: The orientation of the hexes.
+/// - `wrapping`: The axes the map wraps around.
+/// - `plates`: The tectonic plates placement.
+/// - `drift`: The drift of the tectonic plates.
+/// - `hotspots`: The mantle hotspots.
+/// - `mountains`: The detection of the peaks and the mountain ranges.
+/// - `erosion`: The erosion of the terrain.
+/// - `bathymetry`: The shape of the sea floor.
+/// - `hydrology`: The water flowing over the land.
+/// - `climate`: The climate of the map.
+/// - `biomes`: The classification of the hexes into biomes.
+/// - `cryosphere`: The ice sheets and the sea ice.
+/// - `regions`: The continents, islands, oceans and inland seas.
+/// - `noise`: The noise sampled by each generation stage.
+/// - `land_ratio`: The share of hexes above the sea level, between 0 and 1.
+/// - `layer`: The layer drawn in the rendered images.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct GenerateOptions {
     pub seed: u64,
     pub width: u32,
     pub height: u32,
+    #[serde(deserialize_with = "deserialize_orientation")]
     pub orientation: orientation::HexOrientation,
+    pub wrapping: Wrapping,
+    pub plates: PlatesOptions,
+    pub drift: DriftOptions,
+    pub hotspots: HotspotsOptions,
+    pub mountains: MountainsOptions,
+    pub erosion: ErosionOptions,
+    pub bathymetry: BathymetryOptions,
+    pub hydrology: HydrologyOptions,
+    pub climate: ClimateOptions,
+    pub biomes: BiomesOptions,
+    pub cryosphere: CryosphereOptions,
+    pub regions: RegionsOptions,
+    pub noise: NoiseStagesOptions,
+    pub land_ratio: f32,
+    pub layer: Layer,
 }
 
 impl Default for GenerateOptions {
@@ -77,23 +970,394 @@ impl Default for GenerateOptions {
             width: 200,
             height: 160,
             orientation: orientation::HexOrientation::Pointy,
+            wrapping: Wrapping::default(),
+            plates: PlatesOptions::default(),
+            drift: DriftOptions::default(),
+            hotspots: HotspotsOptions::default(),
+            mountains: MountainsOptions::default(),
+            erosion: ErosionOptions::default(),
+            bathymetry: BathymetryOptions::default(),
+            hydrology: HydrologyOptions::default(),
+            climate: ClimateOptions::default(),
+            biomes: BiomesOptions::default(),
+            cryosphere: CryosphereOptions::default(),
+            regions: RegionsOptions::default(),
+            noise: NoiseStagesOptions::default(),
+            land_ratio: 0.29,
+            layer: Layer::default(),
         }
     }
 }
 
+fn parse_orientation(name: &str) -> Option<HexOrientation> {
+    match name {
+        "flat" => Some(HexOrientation::Flat),
+        "pointy" => Some(HexOrientation::Pointy),
+        _ => None,
+    }
+}
+
+fn deserialize_orientation<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HexOrientation, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    parse_orientation(&name)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid orientation: {}", name)))
+}
+
 impl GenerateOptions {
+    /// Reads the options from a TOML file. Missing keys keep their default value.
+    ///
+    /// The `preset`, when given, replaces the one set in the `[noise.elevation]` table, so the
+    /// other keys of the table still override it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, NoiseKind, NoisePreset};
+    ///
+    /// let path = std::env::temp_dir().join("hextergen_config_doctest.toml");
+    /// let config = "width = 50\n[noise.elevation]\npreset = \"pangea\"\noctaves = 8\n";
+    /// std::fs::write(&path, config).unwrap();
+    ///
+    /// let options = GenerateOptions::from_file(&path, None).unwrap();
+    /// assert_eq!(options.width, 50);
+    /// assert_eq!(options.height, 160);
+    /// assert_eq!(options.noise.elevation.frequency, 2.0);
+    /// assert_eq!(options.noise.elevation.octaves, 8);
+    ///
+    /// // A preset given aside goes under the keys of the file
+    /// let config = "[noise.elevation]\nkind = \"warped\"\noctaves = 8\n";
+    /// std::fs::write(&path, config).unwrap();
+    /// let options = GenerateOptions::from_file(&path, Some(NoisePreset::Archipelago)).unwrap();
+    /// assert_eq!(options.noise.elevation.frequency, 12.0);
+    /// assert_eq!(options.noise.elevation.kind, NoiseKind::Warped);
+    /// assert_eq!(options.noise.elevation.octaves, 8);
+    ///
+    /// // Each stage keeps its own defaults under the keys its table overrides
+    /// std::fs::write(&path, "[noise.mountains]\noctaves = 4\n").unwrap();
+    /// let options = GenerateOptions::from_file(&path, None).unwrap();
+    /// assert_eq!(options.noise.mountains.octaves, 4);
+    /// assert_eq!(options.noise.mountains.frequency, 8.0);
+    /// assert_eq!(options.noise.elevation.octaves, 20);
+    ///
+    /// // Misspelled keys are rejected instead of being ignored
+    /// std::fs::write(&path, "[plates]\ncuont = 20\n").unwrap();
+    /// assert!(GenerateOptions::from_file(&path, None).is_err());
+    /// ```
+    pub fn from_file(path: &Path, preset: Option<NoisePreset>) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let mut table: toml::Table = toml::from_str(&content).map_err(|error| error.to_string())?;
+        if let Some(preset) = preset {
+            let preset = toml::Value::try_from(preset).map_err(|error| error.to_string())?;
+            let elevation = table
+                .entry("noise")
+                .or_insert(toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .map(|noise| {
+                    noise
+                        .entry("elevation")
+                        .or_insert(toml::Value::Table(toml::Table::new()))
+                });
+            // A `noise` key that isn't a table is left for the deserialization to reject
+            if let Some(toml::Value::Table(elevation)) = elevation {
+                elevation.insert("preset".to_owned(), preset);
+            }
+        }
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|error| error.to_string())
+    }
+
     pub fn offset_mode(&self) -> OffsetHexMode {
         match self.orientation {
-            HexOrientation::Flat => match self.width % 2 == 0 {
+            HexOrientation::Flat => match self.width.is_multiple_of(2) {
                 true => OffsetHexMode::EvenColumns,
                 false => OffsetHexMode::OddColumns,
             },
-            HexOrientation::Pointy => match self.height % 2 == 0 {
+            HexOrientation::Pointy => match self.height.is_multiple_of(2) {
                 true => OffsetHexMode::EvenRows,
                 false => OffsetHexMode::OddRows,
             },
         }
     }
+
+    /// Checks that the options describe a map that can be generated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, NoiseKind, SeedPlacement};
+    /// use hextergen::mesh::Coord;
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 4,
+    ///     height: 4,
+    ///     ..Default::default()
+    /// };
+    /// assert!(options.validate().is_err());
+    ///
+    /// options.plates.count = 16;
+    /// assert!(options.validate().is_ok());
+    ///
+    /// // The plate count is ignored when the seeds are supplied as a list
+    /// options.plates.count = 75;
+    /// options.plates.placement = SeedPlacement::List(vec![Coord::new(0, 0), Coord::new(3, 3)]);
+    /// assert!(options.validate().is_ok());
+    ///
+    /// // The warp noise can't displace itself
+    /// options.noise.warp.kind = NoiseKind::Warped;
+    /// assert!(options.validate().is_err());
+    /// options.noise.warp.kind = NoiseKind::Fbm;
+    ///
+    /// // Noise parameters that aren't numbers are rejected like the ones out of range
+    /// options.noise.elevation.persistence = f32::NAN;
+    /// assert!(options.validate().is_err());
+    /// options.noise.elevation.persistence = 0.6;
+    /// options.noise.mountains.warp = f32::NAN;
+    /// assert!(options.validate().is_err());
+    /// options.noise.mountains.warp = 0.1;
+    /// assert!(options.validate().is_ok());
+    /// ```
+    ///
+    /// The smallest map is two hexes wide and two hexes high, and a map a single row or column
+    /// wide is rejected.
+    ///
+    /// ```
+    /// use hextergen::climate::Climate;
+    /// use hextergen::cmd::GenerateOptions;
+    /// use hextergen::cryosphere::Cryosphere;
+    /// use hextergen::hydrology::Hydrology;
+    /// use hextergen::mesh::Mesh;
+    /// use hextergen::regions::Regions;
+    /// use hextergen::topography::Topography;
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 2,
+    ///     height: 2,
+    ///     ..Default::default()
+    /// };
+    /// options.plates.count = 1;
+    /// options.hotspots.count = 0;
+    /// assert!(options.validate().is_ok());
+    ///
+    /// let mesh = Mesh::new(&options);
+    /// let mut topography = Topography::new(&options, &mesh);
+    /// let climate = Climate::new(&options, &mesh, &topography);
+    /// Cryosphere::new(&options, &mesh, &mut topography, &climate);
+    /// topography.mountains(&options, &mesh);
+    /// Hydrology::new(&options, &mesh, &topography, &climate);
+    /// Regions::new(&options, &mesh, &topography);
+    ///
+    /// for (width, height) in [(8, 1), (1, 8), (1, 1)] {
+    ///     options.width = width;
+    ///     options.height = height;
+    ///     assert!(options.validate().is_err());
+    /// }
+    /// ```
+    pub fn validate(&self) -> Result<(), String> {
+        // The map is rendered in quadrants around its middle hex, which need a row and a column
+        // on each side of it
+        if self.width < 2 || self.height < 2 {
+            return Err(format!(
+                "Invalid map size: {}x{}, the map must be at least 2x2 hexes",
+                self.width, self.height
+            ));
+        }
+        if let SeedPlacement::List(seeds) = &self.plates.placement {
+            self.validate_seeds(seeds)?;
+        } else if self.plates.count == 0 {
+            return Err("At least one tectonic plate is required".to_owned());
+        } else if self.plates.count > self.width * self.height {
+            return Err(format!(
+                "Cannot place {} plates in a {}x{} map",
+                self.plates.count, self.width, self.height
+            ));
+        }
+        if self.plates.attempts == 0 {
+            return Err("Seed attempts must be greater than 0".to_owned());
+        }
+        if let Some(spacing) = self.plates.spacing {
+            if spacing < 0.0 {
+                return Err(format!("Invalid plate spacing: {}", spacing));
+            }
+        }
+        if self.hotspots.count > self.width * self.height {
+            return Err(format!(
+                "Cannot place {} hotspots in a {}x{} map",
+                self.hotspots.count, self.width, self.height
+            ));
+        }
+        if self.mountains.prominence < 0.0 {
+            return Err(format!(
+                "Invalid peak prominence: {}",
+                self.mountains.prominence
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.mountains.highlands) {
+            return Err(format!(
+                "Invalid highlands share: {}",
+                self.mountains.highlands
+            ));
+        }
+        if self.mountains.size == 0 {
+            return Err("Mountain range size must be greater than 0".to_owned());
+        }
+        if self.drift.step_size <= 0.0 {
+            return Err(format!("Invalid drift step size: {}", self.drift.step_size));
+        }
+        if !(0.0..=1.0).contains(&self.erosion.rate) {
+            return Err(format!("Invalid erosion rate: {}", self.erosion.rate));
+        }
+        if !(0.0..=1.0).contains(&self.erosion.deposition) {
+            return Err(format!(
+                "Invalid deposition rate: {}",
+                self.erosion.deposition
+            ));
+        }
+        if self.erosion.talus < 0.0 {
+            return Err(format!("Invalid talus: {}", self.erosion.talus));
+        }
+        if !(0.0..=1.0).contains(&self.bathymetry.shelf_depth) {
+            return Err(format!(
+                "Invalid shelf depth: {}",
+                self.bathymetry.shelf_depth
+            ));
+        }
+        if !(self.bathymetry.shelf_depth..=1.0).contains(&self.bathymetry.abyss_depth) {
+            return Err(format!(
+                "Invalid abyssal depth: {}",
+                self.bathymetry.abyss_depth
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.bathymetry.relief) {
+            return Err(format!(
+                "Invalid sea floor relief: {}",
+                self.bathymetry.relief
+            ));
+        }
+        if self.hydrology.river_threshold == 0 {
+            return Err("River threshold must be greater than 0".to_owned());
+        }
+        if self.hydrology.evaporation < 0.0 {
+            return Err(format!(
+                "Invalid evaporation: {}",
+                self.hydrology.evaporation
+            ));
+        }
+        if !(0.0..=90.0).contains(&self.climate.tilt) {
+            return Err(format!("Invalid axial tilt: {}", self.climate.tilt));
+        }
+        if !(0.0..=1.0).contains(&self.climate.equator) {
+            return Err(format!("Invalid equator: {}", self.climate.equator));
+        }
+        if self.climate.lapse_rate < 0.0 {
+            return Err(format!("Invalid lapse rate: {}", self.climate.lapse_rate));
+        }
+        if !(0.0..=1.0).contains(&self.climate.moderation) {
+            return Err(format!(
+                "Invalid ocean moderation: {}",
+                self.climate.moderation
+            ));
+        }
+        if self.climate.currents < 0.0 {
+            return Err(format!(
+                "Invalid current temperature: {}",
+                self.climate.currents
+            ));
+        }
+        if !(-1.0..=0.0).contains(&self.biomes.depths) {
+            return Err(format!(
+                "Invalid deep ocean elevation: {}",
+                self.biomes.depths
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.biomes.beach) {
+            return Err(format!("Invalid beach elevation: {}", self.biomes.beach));
+        }
+        if self.biomes.table.is_empty() {
+            return Err("At least one biome rule is required".to_owned());
+        }
+        if self.cryosphere.seasonal < self.cryosphere.pack {
+            return Err(format!(
+                "Invalid seasonal sea ice temperature: {}, under the pack ice temperature",
+                self.cryosphere.seasonal
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.cryosphere.thickness) {
+            return Err(format!(
+                "Invalid ice sheet thickness: {}",
+                self.cryosphere.thickness
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.cryosphere.flattening) {
+            return Err(format!(
+                "Invalid ice sheet flattening: {}",
+                self.cryosphere.flattening
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.regions.continent) {
+            return Err(format!(
+                "Invalid continent share: {}",
+                self.regions.continent
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.regions.ocean) {
+            return Err(format!("Invalid ocean share: {}", self.regions.ocean));
+        }
+        if !(0.0..=1.0).contains(&self.land_ratio) {
+            return Err(format!("Invalid land ratio: {}", self.land_ratio));
+        }
+        if self.noise.warp.kind == NoiseKind::Warped {
+            return Err("The warp noise can't be warped itself".to_owned());
+        }
+        self.validate_noise("elevation", &self.noise.elevation)?;
+        self.validate_noise("mountains", &self.noise.mountains)?;
+        self.validate_noise("warp", &self.noise.warp)
+    }
+
+    fn validate_noise(&self, stage: &str, noise: &NoiseOptions) -> Result<(), String> {
+        if noise.octaves == 0 {
+            return Err(format!("At least one {} noise octave is required", stage));
+        }
+        // NaN fails every comparison, so the values are checked to be finite before their range
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        if !positive(noise.frequency) || !positive(noise.amplitude) {
+            return Err(format!(
+                "The {} noise frequency and amplitude must be finite and greater than 0",
+                stage
+            ));
+        }
+        if !positive(noise.persistence) || !positive(noise.lacunarity) {
+            return Err(format!(
+                "The {} noise persistence and lacunarity must be finite and greater than 0",
+                stage
+            ));
+        }
+        if !noise.warp.is_finite() || noise.warp < 0.0 {
+            return Err(format!("Invalid {} noise warp: {}", stage, noise.warp));
+        }
+        Ok(())
+    }
+
+    fn validate_seeds(&self, seeds: &[Coord]) -> Result<(), String> {
+        if seeds.is_empty() {
+            return Err("At least one tectonic plate is required".to_owned());
+        }
+        for (i, seed) in seeds.iter().enumerate() {
+            if seed.x < 0
+                || seed.y < 0
+                || seed.x >= self.width as i32
+                || seed.y >= self.height as i32
+            {
+                return Err(format!("Seed {},{} is outside the map", seed.x, seed.y));
+            }
+            if seeds[..i].contains(seed) {
+                return Err(format!("Seed {},{} is duplicated", seed.x, seed.y));
+            }
+        }
+        Ok(())
+    }
 }
 
 pub fn run() {
@@ -102,7 +1366,29 @@ pub fn run() {
     let args: Global = argh::from_env();
     match args.nested {
         SubCommands::Generate(generate) => {
-            let mut generate_options = GenerateOptions::default();
+            // The preset goes under the noise keys of the config file, the other flags over them
+            let preset = generate.noise_preset.map(|preset| match preset.as_str() {
+                "archipelago" => NoisePreset::Archipelago,
+                "pangea" => NoisePreset::Pangea,
+                "continents" => NoisePreset::Continents,
+                _ => {
+                    eprintln!("Invalid noise preset: {}", preset);
+                    std::process::exit(1);
+                }
+            });
+            let mut generate_options = match &generate.config {
+                Some(path) => GenerateOptions::from_file(path, preset).unwrap_or_else(|error| {
+                    eprintln!("Invalid config file {}: {}", path.display(), error);
+                    std::process::exit(1);
+                }),
+                None => {
+                    let mut options = GenerateOptions::default();
+                    if let Some(preset) = preset {
+                        options.noise.elevation = preset.options();
+                    }
+                    options
+                }
+            };
             if let Some(seed) = generate.seed {
                 generate_options.seed = seed;
             }
@@ -113,15 +1399,156 @@ pub fn run() {
                 generate_options.height = height;
             }
             if let Some(orientation) = generate.orientation {
-                match orientation.as_str() {
-                    "flat" => generate_options.orientation = orientation::HexOrientation::Flat,
-                    "pointy" => generate_options.orientation = orientation::HexOrientation::Pointy,
-                    _ => {
+                match parse_orientation(&orientation) {
+                    Some(orientation) => generate_options.orientation = orientation,
+                    None => {
                         eprintln!("Invalid orientation: {}", orientation);
                         std::process::exit(1);
                     }
                 }
             }
+            if let Some(wrapping) = generate.wrapping {
+                match wrapping.as_str() {
+                    "flat" => generate_options.wrapping = Wrapping::Flat,
+                    "cylinder" => generate_options.wrapping = Wrapping::Cylinder,
+                    "torus" => generate_options.wrapping = Wrapping::Torus,
+                    _ => {
+                        eprintln!("Invalid wrapping: {}", wrapping);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Some(plates) = generate.plates {
+                generate_options.plates.count = plates;
+            }
+            if let Some(spacing) = generate.plate_spacing {
+                generate_options.plates.spacing = Some(spacing);
+            }
+            if let Some(attempts) = generate.seed_attempts {
+                generate_options.plates.attempts = attempts;
+            }
+            if let Some(strategy) = generate.seed_strategy {
+                match strategy.as_str() {
+                    "random" => generate_options.plates.placement = SeedPlacement::Random,
+                    "poisson" => generate_options.plates.placement = SeedPlacement::Poisson,
+                    "jittered" => generate_options.plates.placement = SeedPlacement::Jittered,
+                    _ => {
+                        eprintln!("Invalid seed strategy: {}", strategy);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Some(path) = generate.seeds_file {
+                match seeds::read(&path) {
+                    Ok(seeds) => generate_options.plates.placement = SeedPlacement::List(seeds),
+                    Err(error) => {
+                        eprintln!("Invalid seeds file {}: {}", path.display(), error);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Some(kind) = generate.noise_kind {
+                match kind.as_str() {
+                    "fbm" => generate_options.noise.elevation.kind = NoiseKind::Fbm,
+                    "ridged" => generate_options.noise.elevation.kind = NoiseKind::Ridged,
+                    "billow" => generate_options.noise.elevation.kind = NoiseKind::Billow,
+                    "worley" => generate_options.noise.elevation.kind = NoiseKind::Worley,
+                    "warped" => generate_options.noise.elevation.kind = NoiseKind::Warped,
+                    _ => {
+                        eprintln!("Invalid noise kind: {}", kind);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Some(frequency) = generate.frequency {
+                generate_options.noise.elevation.frequency = frequency;
+            }
+            if let Some(amplitude) = generate.amplitude {
+                generate_options.noise.elevation.amplitude = amplitude;
+            }
+            if let Some(octaves) = generate.octaves {
+                generate_options.noise.elevation.octaves = octaves;
+            }
+            if let Some(persistence) = generate.persistence {
+                generate_options.noise.elevation.persistence = persistence;
+            }
+            if let Some(lacunarity) = generate.lacunarity {
+                generate_options.noise.elevation.lacunarity = lacunarity;
+            }
+            if let Some(warp) = generate.warp {
+                generate_options.noise.elevation.warp = warp;
+            }
+            if let Some(land_ratio) = generate.land_ratio {
+                generate_options.land_ratio = land_ratio;
+            }
+            if let Some(steps) = generate.drift_steps {
+                generate_options.drift.steps = steps;
+            }
+            if let Some(step_size) = generate.drift_step_size {
+                generate_options.drift.step_size = step_size;
+            }
+            if let Some(hotspots) = generate.hotspots {
+                generate_options.hotspots.count = hotspots;
+            }
+            if let Some(trail) = generate.hotspot_trail {
+                generate_options.hotspots.trail = trail;
+            }
+            if let Some(droplets) = generate.erosion_droplets {
+                generate_options.erosion.droplets = droplets;
+            }
+            if let Some(rate) = generate.erosion_rate {
+                generate_options.erosion.rate = rate;
+            }
+            if let Some(deposition) = generate.deposition_rate {
+                generate_options.erosion.deposition = deposition;
+            }
+            if let Some(iterations) = generate.thermal_iterations {
+                generate_options.erosion.thermal_iterations = iterations;
+            }
+            if let Some(talus) = generate.talus {
+                generate_options.erosion.talus = talus;
+            }
+            if let Some(threshold) = generate.river_threshold {
+                generate_options.hydrology.river_threshold = threshold;
+            }
+            if let Some(evaporation) = generate.evaporation {
+                generate_options.hydrology.evaporation = evaporation;
+            }
+            if let Some(layer) = generate.layer {
+                match layer.as_str() {
+                    "debug" => generate_options.layer = Layer::Debug,
+                    "basins" => generate_options.layer = Layer::Basins,
+                    "temperature" => generate_options.layer = Layer::Temperature,
+                    "precipitation" => generate_options.layer = Layer::Precipitation,
+                    "biomes" => generate_options.layer = Layer::Biomes,
+                    "currents" => generate_options.layer = Layer::Currents,
+                    "regions" => generate_options.layer = Layer::Regions,
+                    _ => {
+                        eprintln!("Invalid layer: {}", layer);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Some(tilt) = generate.tilt {
+                generate_options.climate.tilt = tilt;
+            }
+            if let Some(equator) = generate.equator {
+                generate_options.climate.equator = equator;
+            }
+            if let Some(lapse_rate) = generate.lapse_rate {
+                generate_options.climate.lapse_rate = lapse_rate;
+            }
+            if let Some(moderation) = generate.ocean_moderation {
+                generate_options.climate.moderation = moderation;
+            }
+            if let Some(currents) = generate.current_temperature {
+                generate_options.climate.currents = currents;
+            }
+
+            if let Err(error) = generate_options.validate() {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
 
             generate::generate_map(&generate_options);
         }
@@ -0,0 +1,115 @@
+use crate::{
+    climate::Climate,
+    cmd::GenerateOptions,
+    mesh::{steps, Coord, Mesh},
+    topography::Topography,
+};
+use log::debug;
+use ndarray::{Array2, Zip};
+
+// Distance, in hexes from their margin, over which the ice sheets rise to their full thickness
+const DOME_REACH: f32 = 10.0;
+
+/// The ice covering a hex.
+///
+/// - `Sheet`: Land buried under an ice sheet all year.
+/// - `Pack`: Sea frozen all year.
+/// - `Seasonal`: Sea frozen in winter and open in summer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ice {
+    Sheet,
+    Pack,
+    Seasonal,
+}
+
+/// The ice covering the map.
+///
+/// # Fields
+/// - `ice`: The ice covering each hex, `None` for the hexes free of ice.
+/// - `thickness`: The elevation the ice sheet adds on top of the land of each hex, 0 out of the
+///   ice sheets.
+pub struct Cryosphere {
+    pub ice: Array2<Option<Ice>>,
+    pub thickness: Array2<f32>,
+}
+
+impl Cryosphere {
+    // Cover the cold hexes with ice, and flatten the land under the ice sheets to their surface
+    // The ice sheets rise as domes from their margin, with the square root of the distance to it
+    // like an ice sheet spreading under its own weight, burying the valleys and the lower
+    // mountains under a smooth surface. The glaciers grind down a share of the mountains standing
+    // out of the ice
+    pub fn new(
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        topography: &mut Topography,
+        climate: &Climate,
+    ) -> Self {
+        let cryosphere = &options.cryosphere;
+        let dim = topography.elevations.dim();
+        let ice = Array2::from_shape_fn(dim, |(x, y)| {
+            let temperature = climate.temperature[[x, y]];
+            match topography.is_land(x as i32, y as i32) {
+                true if temperature < cryosphere.sheet => Some(Ice::Sheet),
+                true => None,
+                false if temperature < cryosphere.pack => Some(Ice::Pack),
+                false if temperature < cryosphere.seasonal => Some(Ice::Seasonal),
+                false => None,
+            }
+        });
+
+        let distances = margin_distance(mesh, &ice);
+        let surface = distances.mapv(|distance| {
+            distance.map(|distance| cryosphere.thickness * (distance / DOME_REACH).min(1.0).sqrt())
+        });
+        let thickness = Array2::from_shape_fn(dim, |(x, y)| match surface[[x, y]] {
+            Some(surface) => (surface - topography.elevations[[x, y]]).max(0.0),
+            None => 0.0,
+        });
+        Zip::from(&mut topography.elevations)
+            .and(&surface)
+            .for_each(|elevation, surface| match surface {
+                Some(surface) if *elevation > *surface => {
+                    *elevation = surface + (*elevation - surface) * (1.0 - cryosphere.flattening);
+                }
+                _ => {}
+            });
+        topography.elevations += &thickness;
+
+        let count = |kind: Ice| ice.iter().filter(|ice| **ice == Some(kind)).count();
+        debug!(
+            "Ice sheets: {} hexes, pack ice: {} hexes, seasonal ice: {} hexes",
+            count(Ice::Sheet),
+            count(Ice::Pack),
+            count(Ice::Seasonal)
+        );
+
+        Self { ice, thickness }
+    }
+}
+
+// Distance, in hexes, from each ice sheet hex to the closest hex out of the ice sheets
+// The hexes at the margin of the sheets are at 1, and the edges of the map don't count as a
+// margin, as they lie at the poles. It is `None` out of the ice sheets
+fn margin_distance(mesh: &Mesh, ice: &Array2<Option<Ice>>) -> Array2<Option<f32>> {
+    let sheet = |coord: &Coord| ice[coord.to_dim()] == Some(Ice::Sheet);
+    let margins: Vec<Coord> = ice
+        .indexed_iter()
+        .map(|((x, y), _ice)| Coord::new(x as i32, y as i32))
+        .filter(|coord| {
+            sheet(coord)
+                && mesh
+                    .get_hex(coord.x, coord.y)
+                    .neighbors
+                    .iter()
+                    .any(|(n_coord, _wrapping)| !sheet(n_coord))
+        })
+        .collect();
+
+    let reached = steps(mesh, margins, u32::MAX, sheet);
+    Array2::from_shape_fn(ice.dim(), |(x, y)| {
+        reached
+            .get(&Coord::new(x as i32, y as i32))
+            .map(|steps| *steps as f32 + 1.0)
+    })
+}
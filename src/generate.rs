@@ -1,7 +1,11 @@
 use log::info;
 
+use crate::climate::Climate;
 use crate::cmd::GenerateOptions;
+use crate::cryosphere::Cryosphere;
+use crate::hydrology::Hydrology;
 use crate::mesh::Mesh;
+use crate::regions::Regions;
 use crate::render;
 use crate::topography::Topography;
 use crate::utils::id;
@@ -16,10 +20,38 @@ pub fn generate_map(options: &GenerateOptions) {
     info!("Mesh generated in {}ms", start.elapsed().as_millis());
 
     let start = std::time::Instant::now();
-    let topography = Topography::new(options, &mesh);
+    let mut topography = Topography::new(options, &mesh);
     info!("Topography generated in {}ms", start.elapsed().as_millis());
 
     let start = std::time::Instant::now();
-    render::quadrants(&mesh, &topography);
+    let climate = Climate::new(options, &mesh, &topography);
+    info!("Climate generated in {}ms", start.elapsed().as_millis());
+
+    let start = std::time::Instant::now();
+    let cryosphere = Cryosphere::new(options, &mesh, &mut topography, &climate);
+    info!("Cryosphere generated in {}ms", start.elapsed().as_millis());
+
+    let start = std::time::Instant::now();
+    topography.mountains(options, &mesh);
+    info!("Mountains found in {}ms", start.elapsed().as_millis());
+
+    let start = std::time::Instant::now();
+    let hydrology = Hydrology::new(options, &mesh, &topography, &climate);
+    info!("Hydrology generated in {}ms", start.elapsed().as_millis());
+
+    let start = std::time::Instant::now();
+    let regions = Regions::new(options, &mesh, &topography);
+    info!("Regions generated in {}ms", start.elapsed().as_millis());
+
+    let start = std::time::Instant::now();
+    let map = render::Map {
+        mesh: &mesh,
+        topography: &topography,
+        climate: &climate,
+        cryosphere: &cryosphere,
+        hydrology: &hydrology,
+        regions: &regions,
+    };
+    render::quadrants(&map, options.layer);
     info!("Rendered quadrants in {}ms", start.elapsed().as_millis());
 }
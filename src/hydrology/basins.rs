@@ -0,0 +1,32 @@
+use crate::mesh::Coord;
+use ndarray::Array2;
+
+/// The drainage basin of each hex, labeled by the place its water ends in.
+///
+/// Land hexes draining into the same coastal hex, or into the same endorheic lake, share a
+/// basin, numbered from 1. Water hexes belong to no basin and are labeled 0.
+pub type Basins = Array2<u32>;
+
+pub(super) trait BasinsBuilder {
+    fn build(flow: &Array2<Option<Coord>>, order: &[Coord]) -> Basins;
+}
+
+impl BasinsBuilder for Basins {
+    // Walk the land hexes from downstream to upstream, so each hex takes the basin of the hex it
+    // drains into
+    // A hex draining into the sea, or not draining at all, starts a new basin
+    fn build(flow: &Array2<Option<Coord>>, order: &[Coord]) -> Basins {
+        let mut basins = Basins::zeros(flow.dim());
+        let mut count = 0;
+        for coord in order.iter().rev() {
+            basins[coord.to_dim()] = match flow[coord.to_dim()] {
+                Some(next) if basins[next.to_dim()] > 0 => basins[next.to_dim()],
+                _ => {
+                    count += 1;
+                    count
+                }
+            };
+        }
+        basins
+    }
+}
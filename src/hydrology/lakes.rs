@@ -0,0 +1,191 @@
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{steps, Coord, Mesh},
+    topography::Topography,
+};
+use ndarray::Array2;
+
+// Difference under which two water surfaces are considered the same
+const SURFACE_TOLERANCE: f32 = 1e-6;
+
+/// A depression of the land filled with water.
+///
+/// When the water evaporated from the lake exceeds the rain falling on its catchment, the lake
+/// never reaches its outlet and the basin is endorheic, leaving a salt flat.
+///
+/// # Fields
+/// - `hexes`: The hexes covered by the lake.
+/// - `surface`: The elevation of the water surface, the elevation of the lowest pass out of the
+///   depression.
+/// - `outlet`: The hex right outside the lake through which it drains.
+/// - `inflow`: The water flowing into the lake, the precipitation falling on the hexes draining
+///   into it, including its own, summed in millimeters per year.
+/// - `endorheic`: Whether the lake doesn't drain, and is a salt flat instead.
+///
+/// # Examples
+///
+/// A pit in a valley fills up to the lowest of its neighbors, the spill point through which it
+/// drains down the valley.
+///
+/// ```
+/// use hextergen::climate::Climate;
+/// use hextergen::cmd::{GenerateOptions, Wrapping};
+/// use hextergen::hydrology::Hydrology;
+/// use hextergen::mesh::{Coord, Mesh};
+/// use hextergen::topography::Topography;
+/// use ndarray::Array2;
+///
+/// let mut options = GenerateOptions {
+///     width: 9,
+///     height: 4,
+///     wrapping: Wrapping::Flat,
+///     ..Default::default()
+/// };
+/// options.plates.count = 1;
+/// let mesh = Mesh::new(&options);
+/// let mut topography = Topography::new(&options, &mesh);
+/// topography.elevations = Array2::from_shape_fn((9, 4), |(x, y)| match (x, y) {
+///     (_, 3) => -0.5,
+///     (4, 2) => 0.1,
+///     (2, 1) => 0.05,
+///     (x, 1) => 0.2 + 0.02 * (x as f32 - 4.0).abs(),
+///     _ => 0.9,
+/// });
+///
+/// let mut climate = Climate::new(&options, &mesh, &topography);
+/// climate.precipitation = Array2::from_elem((9, 4), 500.0);
+///
+/// options.hydrology.evaporation = 2000.0;
+/// let hydrology = Hydrology::new(&options, &mesh, &topography, &climate);
+/// assert_eq!(hydrology.lakes.len(), 1);
+/// let lake = &hydrology.lakes[0];
+/// assert_eq!(lake.hexes, vec![Coord::new(2, 1)]);
+/// assert_eq!(lake.outlet, Coord::new(3, 1));
+/// assert_eq!(lake.surface, topography.elevations[[3, 1]]);
+/// assert_eq!(hydrology.flow[[2, 1]], Some(lake.outlet));
+/// assert!(!lake.endorheic);
+///
+/// // The rain of five hexes drains into the lake, which dries up when it evaporates more
+/// assert_eq!(lake.inflow, 2500.0);
+/// options.hydrology.evaporation = 3000.0;
+/// let hydrology = Hydrology::new(&options, &mesh, &topography, &climate);
+/// assert!(hydrology.lakes[0].endorheic);
+/// assert_eq!(hydrology.flow[[2, 1]], None);
+///
+/// // A drier climate leaves the same lake a salt flat
+/// options.hydrology.evaporation = 2000.0;
+/// climate.precipitation = Array2::from_elem((9, 4), 300.0);
+/// let hydrology = Hydrology::new(&options, &mesh, &topography, &climate);
+/// assert_eq!(hydrology.lakes[0].inflow, 1500.0);
+/// assert!(hydrology.lakes[0].endorheic);
+/// ```
+#[derive(Debug)]
+pub struct Lake {
+    pub hexes: Vec<Coord>,
+    pub surface: f32,
+    pub outlet: Coord,
+    pub inflow: f32,
+    pub endorheic: bool,
+}
+
+impl Lake {
+    /// The number of hexes covered by the lake.
+    pub fn area(&self) -> usize {
+        self.hexes.len()
+    }
+
+    // Group the flooded land hexes into lakes
+    // A hex is flooded when the water surface is above its elevation, and neighbor flooded hexes
+    // with the same surface belong to the same lake
+    // The lakes are sorted from the highest surface to the lowest, so the lakes draining into
+    // others come first
+    pub(super) fn detect(
+        mesh: &Mesh,
+        topography: &Topography,
+        surface: &Array2<f32>,
+        flow: &Array2<Option<Coord>>,
+        runoff: &Array2<f32>,
+    ) -> Vec<Lake> {
+        let flooded = |coord: &Coord| {
+            topography.is_land(coord.x, coord.y)
+                && surface[coord.to_dim()] > topography.elevations[coord.to_dim()]
+        };
+        // Index of the lake each flooded hex was grouped into
+        let mut labels = Array2::from_elem(surface.dim(), None);
+        let mut lakes = Vec::new();
+
+        for ((x, y), level) in surface.indexed_iter() {
+            let start = Coord::new(x as i32, y as i32);
+            if labels[start.to_dim()].is_some() || !flooded(&start) {
+                continue;
+            }
+
+            let label = Some(lakes.len());
+            let reached = steps(mesh, [start], u32::MAX, |coord| {
+                labels[coord.to_dim()].is_none()
+                    && flooded(coord)
+                    && (surface[coord.to_dim()] - level).abs() < SURFACE_TOLERANCE
+            });
+            let mut hexes: Vec<Coord> = reached.into_keys().collect();
+            hexes.sort_by_key(|coord| (coord.y, coord.x));
+            hexes.iter().for_each(|coord| labels[coord.to_dim()] = label);
+
+            // Follow the flow until it leaves the lake
+            let mut exit = start;
+            while let Some(next) = flow[exit.to_dim()] {
+                if labels[next.to_dim()] != label {
+                    break;
+                }
+                exit = next;
+            }
+            let outlet = flow[exit.to_dim()].unwrap_or(exit);
+
+            lakes.push(Lake {
+                inflow: runoff[exit.to_dim()],
+                hexes,
+                surface: *level,
+                outlet,
+                endorheic: false,
+            });
+        }
+
+        lakes.sort_by(|a, b| b.surface.total_cmp(&a.surface));
+        lakes
+    }
+
+    // Mark the lakes evaporating more water than the rain on their catchment brings as endorheic
+    // Their water no longer reaches the outlet, so their catchment and its rain are removed
+    // downstream
+    pub(super) fn evaporate(
+        lakes: &mut [Lake],
+        options: &GenerateOptions,
+        flow: &mut Array2<Option<Coord>>,
+        accumulation: &mut Array2<f32>,
+        runoff: &mut Array2<f32>,
+    ) {
+        for lake in lakes.iter_mut() {
+            let exit = lake
+                .hexes
+                .iter()
+                .copied()
+                .find(|hex| flow[hex.to_dim()] == Some(lake.outlet));
+            let Some(exit) = exit else {
+                continue;
+            };
+            lake.inflow = runoff[exit.to_dim()];
+            if lake.inflow > options.hydrology.evaporation * lake.area() as f32 {
+                continue;
+            }
+
+            lake.endorheic = true;
+            let catchment = accumulation[exit.to_dim()];
+            let mut current = Some(lake.outlet);
+            while let Some(hex) = current {
+                accumulation[hex.to_dim()] -= catchment;
+                runoff[hex.to_dim()] -= lake.inflow;
+                current = flow[hex.to_dim()];
+            }
+            flow[exit.to_dim()] = None;
+        }
+    }
+}
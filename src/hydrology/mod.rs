@@ -0,0 +1,276 @@
+mod basins;
+mod lakes;
+mod rivers;
+
+pub use basins::Basins;
+use basins::BasinsBuilder;
+pub use lakes::Lake;
+pub use rivers::River;
+
+use crate::{
+    climate::Climate,
+    cmd::GenerateOptions,
+    mesh::{Coord, Mesh},
+    topography::Topography,
+};
+use log::debug;
+use ndarray::Array2;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// Rise of the filled surface between two hexes, so the water can flow across flats
+const EPSILON: f32 = 1e-5;
+
+// Hex in the priority-flood queue, ordered by its lowest elevation first
+// Ties are broken by the coordinates, so the flood is deterministic
+#[derive(PartialEq)]
+struct Flooded {
+    elevation: f32,
+    coord: Coord,
+}
+
+impl Eq for Flooded {}
+
+impl Ord for Flooded {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .elevation
+            .total_cmp(&self.elevation)
+            .then_with(|| (other.coord.x, other.coord.y).cmp(&(self.coord.x, self.coord.y)))
+    }
+}
+
+impl PartialOrd for Flooded {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The water flowing over the land.
+///
+/// # Fields
+/// - `filled`: The elevations with their depressions filled, so every land hex can drain to the
+///   sea. The surface rises slightly across the filled depressions and flats.
+/// - `flow`: The neighbor each land hex drains into, following the steepest descent of the filled
+///   surface. Water hexes have no flow.
+/// - `accumulation`: The number of hexes draining through each hex, including itself.
+/// - `runoff`: The precipitation falling on the hexes draining through each hex, including
+///   itself, summed in millimeters per year.
+/// - `lakes`: The lakes filling the depressions of the land.
+/// - `lake_map`: The index in `lakes` of the lake covering each hex.
+/// - `rivers`: The rivers, split into stretches of the same Strahler order.
+/// - `basins`: The drainage basin of each hex.
+///
+/// # Examples
+///
+/// Two valleys running along the second row join above the coast, into a river of order 2. The
+/// high land around them drains into the valleys, or straight into the sea on the last row.
+///
+/// ```
+/// use hextergen::climate::Climate;
+/// use hextergen::cmd::{GenerateOptions, Wrapping};
+/// use hextergen::hydrology::Hydrology;
+/// use hextergen::mesh::{Coord, Mesh};
+/// use hextergen::topography::Topography;
+/// use ndarray::Array2;
+///
+/// let mut options = GenerateOptions {
+///     width: 9,
+///     height: 4,
+///     wrapping: Wrapping::Flat,
+///     ..Default::default()
+/// };
+/// options.plates.count = 1;
+/// options.hydrology.river_threshold = 2;
+/// let mesh = Mesh::new(&options);
+/// let mut topography = Topography::new(&options, &mesh);
+/// topography.elevations = Array2::from_shape_fn((9, 4), |(x, y)| match (x, y) {
+///     (_, 3) => -0.5,
+///     (4, 2) => 0.1,
+///     (x, 1) => 0.2 + 0.02 * (x as f32 - 4.0).abs(),
+///     _ => 0.9,
+/// });
+/// let climate = Climate::new(&options, &mesh, &topography);
+/// let hydrology = Hydrology::new(&options, &mesh, &topography, &climate);
+///
+/// // Every land hex drains to the sea
+/// for ((x, y), _) in topography.elevations.indexed_iter() {
+///     let mut current = Coord::new(x as i32, y as i32);
+///     while let Some(next) = hydrology.flow[current.to_dim()] {
+///         current = next;
+///     }
+///     assert!(!topography.is_land(current.x, current.y));
+/// }
+///
+/// // Both valleys meet on the coast, which drains the whole land above it
+/// let confluence = Coord::new(4, 2);
+/// assert_eq!(hydrology.flow[[3, 1]], Some(Coord::new(4, 1)));
+/// assert_eq!(hydrology.flow[[4, 1]], Some(confluence));
+/// assert_eq!(hydrology.flow[[5, 1]], Some(confluence));
+/// assert_eq!(hydrology.accumulation[confluence.to_dim()], 19.0);
+///
+/// // Two rivers of order 1 flow into the confluence, and a river of order 2 leaves it
+/// let orders: Vec<(u32, Coord, Coord)> = hydrology
+///     .rivers
+///     .iter()
+///     .map(|river| (river.order, river.hexes[0], *river.hexes.last().unwrap()))
+///     .collect();
+/// assert_eq!(orders.len(), 3);
+/// assert!(orders.contains(&(1, Coord::new(1, 1), confluence)));
+/// assert!(orders.contains(&(1, Coord::new(8, 1), confluence)));
+/// assert!(orders.iter().any(|(order, start, _)| *order == 2 && *start == confluence));
+/// ```
+pub struct Hydrology {
+    pub filled: Array2<f32>,
+    pub flow: Array2<Option<Coord>>,
+    pub accumulation: Array2<f32>,
+    pub runoff: Array2<f32>,
+    pub lakes: Vec<Lake>,
+    pub lake_map: Array2<Option<usize>>,
+    pub rivers: Vec<River>,
+    pub basins: Basins,
+}
+
+impl Hydrology {
+    pub fn new(
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        topography: &Topography,
+        climate: &Climate,
+    ) -> Self {
+        let filled = Self::fill(mesh, topography, EPSILON);
+        let surface = Self::fill(mesh, topography, 0.0);
+        let mut flow = Self::flow(mesh, topography, &filled);
+        let order = Self::downstream_order(topography, &filled);
+        let mut accumulation = Self::accumulation(&flow, &order, &Array2::ones(flow.dim()));
+        let mut runoff = Self::accumulation(&flow, &order, &climate.precipitation);
+
+        let mut lakes = Lake::detect(mesh, topography, &surface, &flow, &runoff);
+        Lake::evaporate(&mut lakes, options, &mut flow, &mut accumulation, &mut runoff);
+        let mut lake_map = Array2::from_elem(flow.dim(), None);
+        for (i, lake) in lakes.iter().enumerate() {
+            for hex in &lake.hexes {
+                lake_map[hex.to_dim()] = Some(i);
+            }
+        }
+
+        let rivers = River::extract(options, &flow, &accumulation, &order, &lake_map);
+        let basins = Basins::build(&flow, &order);
+        debug!(
+            "Lakes: {}, rivers: {}, basins: {}",
+            lakes.len(),
+            rivers.len(),
+            basins.iter().max().unwrap_or(&0)
+        );
+
+        Self {
+            filled,
+            flow,
+            accumulation,
+            runoff,
+            lakes,
+            lake_map,
+            rivers,
+            basins,
+        }
+    }
+
+    // Fill the depressions of the terrain with a priority-flood from the sea
+    // The hexes are visited from the lowest to the highest, starting at the water hexes, and each
+    // land hex is raised `epsilon` above the hex it was reached from when it is lower
+    // Without `epsilon`, the depressions are filled flat up to their water surface
+    // When the map has no water, the flood starts at its lowest hex
+    fn fill(mesh: &Mesh, topography: &Topography, epsilon: f32) -> Array2<f32> {
+        let mut filled = topography.elevations.clone();
+        let mut closed = Array2::from_elem(filled.dim(), false);
+        let mut queue = BinaryHeap::<Flooded>::new();
+
+        for ((x, y), elevation) in filled.indexed_iter() {
+            if *elevation <= 0.0 {
+                let coord = Coord::new(x as i32, y as i32);
+                queue.push(Flooded {
+                    elevation: *elevation,
+                    coord,
+                });
+                closed[coord.to_dim()] = true;
+            }
+        }
+        if queue.is_empty() {
+            let ((x, y), elevation) = filled
+                .indexed_iter()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap();
+            let coord = Coord::new(x as i32, y as i32);
+            queue.push(Flooded {
+                elevation: *elevation,
+                coord,
+            });
+            closed[coord.to_dim()] = true;
+        }
+
+        while let Some(Flooded { elevation, coord }) = queue.pop() {
+            for (n_coord, _wrapping) in &mesh.get_hex(coord.x, coord.y).neighbors {
+                if closed[n_coord.to_dim()] {
+                    continue;
+                }
+                closed[n_coord.to_dim()] = true;
+                let n_elevation = &mut filled[n_coord.to_dim()];
+                *n_elevation = n_elevation.max(elevation + epsilon);
+                queue.push(Flooded {
+                    elevation: *n_elevation,
+                    coord: *n_coord,
+                });
+            }
+        }
+
+        filled
+    }
+
+    // Drain each land hex into its lowest neighbor on the filled surface
+    fn flow(mesh: &Mesh, topography: &Topography, filled: &Array2<f32>) -> Array2<Option<Coord>> {
+        Array2::from_shape_fn(filled.dim(), |(x, y)| {
+            let coord = Coord::new(x as i32, y as i32);
+            if !topography.is_land(coord.x, coord.y) {
+                return None;
+            }
+            mesh.get_hex(coord.x, coord.y)
+                .neighbors
+                .iter()
+                .map(|(n_coord, _wrapping)| *n_coord)
+                .filter(|n_coord| filled[n_coord.to_dim()] < filled[coord.to_dim()])
+                .min_by(|a, b| filled[a.to_dim()].total_cmp(&filled[b.to_dim()]))
+        })
+    }
+
+    // Land hexes sorted from the highest to the lowest on the filled surface, so every hex comes
+    // before the hexes it drains into
+    fn downstream_order(topography: &Topography, filled: &Array2<f32>) -> Vec<Coord> {
+        let mut order: Vec<Coord> = filled
+            .indexed_iter()
+            .map(|((x, y), _)| Coord::new(x as i32, y as i32))
+            .filter(|coord| topography.is_land(coord.x, coord.y))
+            .collect();
+        order.sort_by(|a, b| {
+            filled[b.to_dim()]
+                .total_cmp(&filled[a.to_dim()])
+                .then_with(|| (a.x, a.y).cmp(&(b.x, b.y)))
+        });
+        order
+    }
+
+    // Sum a value of the hexes draining through each hex, like their count or their rain
+    fn accumulation(
+        flow: &Array2<Option<Coord>>,
+        order: &[Coord],
+        values: &Array2<f32>,
+    ) -> Array2<f32> {
+        let mut accumulation = Array2::<f32>::zeros(flow.dim());
+        for coord in order {
+            accumulation[coord.to_dim()] += values[coord.to_dim()];
+            if let Some(next) = flow[coord.to_dim()] {
+                accumulation[next.to_dim()] += accumulation[coord.to_dim()];
+            }
+        }
+        accumulation
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{cmd::GenerateOptions, mesh::Coord};
+use ndarray::Array2;
+
+/// A stretch of river with the same Strahler order.
+///
+/// Rivers of order 1 start at their sources, and two rivers of the same order join into a river
+/// of the next order. A river joined by a lower order one keeps its order.
+///
+/// # Fields
+/// - `hexes`: The hexes of the stretch from upstream to downstream. The last hex is where the
+///   stretch flows into a river of another order or into the water.
+/// - `discharge`: The number of hexes draining through the last land hex of the stretch.
+/// - `order`: The Strahler order of the stretch.
+#[derive(Debug)]
+pub struct River {
+    pub hexes: Vec<Coord>,
+    pub discharge: f32,
+    pub order: u32,
+}
+
+impl River {
+    // Extract the hexes draining more than the threshold as rivers
+    // The `order` lists the land hexes from upstream to downstream
+    // Rivers end at the lakes, and the rivers leaving the lakes start again from their outlet
+    pub(super) fn extract(
+        options: &GenerateOptions,
+        flow: &Array2<Option<Coord>>,
+        accumulation: &Array2<f32>,
+        order: &[Coord],
+        lake_map: &Array2<Option<usize>>,
+    ) -> Vec<River> {
+        let threshold = options.hydrology.river_threshold as f32;
+        let is_river = |coord: &Coord| {
+            flow[coord.to_dim()].is_some()
+                && lake_map[coord.to_dim()].is_none()
+                && accumulation[coord.to_dim()] >= threshold
+        };
+
+        // Highest Strahler order flowing into each hex, and how many inflows have it
+        let mut inflows = Array2::<(u32, u32)>::from_elem(flow.dim(), (0, 0));
+        let mut strahler = Array2::<u32>::zeros(flow.dim());
+        for coord in order.iter().filter(|coord| is_river(coord)) {
+            let (highest, count) = inflows[coord.to_dim()];
+            let stretch = match (highest, count) {
+                (0, _) => 1,
+                (_, 1) => highest,
+                _ => highest + 1,
+            };
+            strahler[coord.to_dim()] = stretch;
+
+            if let Some(next) = flow[coord.to_dim()] {
+                let inflow = &mut inflows[next.to_dim()];
+                match stretch.cmp(&inflow.0) {
+                    std::cmp::Ordering::Greater => *inflow = (stretch, 1),
+                    std::cmp::Ordering::Equal => inflow.1 += 1,
+                    std::cmp::Ordering::Less => {}
+                }
+            }
+        }
+
+        // A stretch starts on every hex without an inflow of its own order
+        order
+            .iter()
+            .filter(|coord| is_river(coord))
+            .filter(|coord| inflows[coord.to_dim()].0 != strahler[coord.to_dim()])
+            .map(|source| {
+                let stretch = strahler[source.to_dim()];
+                let mut hexes = vec![*source];
+                let mut current = *source;
+                while let Some(next) = flow[current.to_dim()] {
+                    hexes.push(next);
+                    if !is_river(&next) || strahler[next.to_dim()] != stretch {
+                        break;
+                    }
+                    current = next;
+                }
+                River {
+                    hexes,
+                    discharge: accumulation[current.to_dim()],
+                    order: stretch,
+                }
+            })
+            .collect()
+    }
+}
@@ -1,6 +1,10 @@
+pub mod climate;
 pub mod cmd;
+pub mod cryosphere;
 pub mod mesh;
 pub mod generate;
+pub mod hydrology;
+pub mod regions;
 pub mod render;
 pub mod topography;
 pub mod utils;
@@ -1,8 +1,13 @@
-use crate::cmd::GenerateOptions;
+use crate::cmd::{GenerateOptions, Wrapping};
 use hashbrown::HashMap;
-use hexx::{Hex as Hexx, HexLayout, OffsetHexMode, Vec2};
+use hexx::{Hex as Hexx, HexLayout, HexOrientation, OffsetHexMode, Vec2};
+use log::warn;
 use ndarray::{Array2, Dim};
 use rayon::prelude::*;
+use serde::Deserialize;
+use std::{collections::VecDeque, hash::Hash};
+
+pub mod sphere;
 
 type WrapAround = bool;
 
@@ -22,7 +27,7 @@ type WrapAround = bool;
 /// assert_eq!(coord.x, 10);
 /// assert_eq!(coord.y, 20);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub struct Coord {
     pub x: i32,
     pub y: i32,
@@ -43,7 +48,7 @@ impl Coord {
     }
 
     /// Converts `Coord` into an array of two integers.
-    fn to_array(&self) -> [i32; 2] {
+    fn to_array(self) -> [i32; 2] {
         [self.x, self.y]
     }
 
@@ -52,12 +57,18 @@ impl Coord {
         Dim([self.x as usize, self.y as usize])
     }
 
-    /// Wraps the x-coordinate around a given width, simulating a toroidal (wrap-around) grid.
-    fn wrap_x(&self, width: i32) -> Self {
-        Coord {
-            x: (self.x + width) % width,
-            y: self.y,
-        }
+    /// Wraps the coordinate around the axes the map wraps around.
+    ///
+    /// Returns `None` when the coordinate falls off an edge of the map that doesn't wrap.
+    fn wrap(&self, width: i32, height: i32, wrapping: Wrapping) -> Option<Self> {
+        let wrap_axis = |value: i32, size: i32, wraps: bool| match wraps {
+            true => Some(value.rem_euclid(size)),
+            false => (0..size).contains(&value).then_some(value),
+        };
+        Some(Coord {
+            x: wrap_axis(self.x, width, wrapping.wraps_x())?,
+            y: wrap_axis(self.y, height, wrapping.wraps_y())?,
+        })
     }
 
     /// Returns a new `Coord` displaced by `dx` and `dy` from the original.
@@ -79,6 +90,8 @@ impl Coord {
         }
     }
 
+    /// Maps the coordinate onto a cylinder of circumference 1 and height 1, so the noise sampled
+    /// on it tiles seamlessly around the x axis.
     pub fn to_cilinder(&self, width: i32, height: i32) -> (f32, f32, f32) {
         let nx = self.x as f32 / width as f32;
         let ny = self.y as f32 / height as f32;
@@ -88,6 +101,25 @@ impl Coord {
         let cyl_z = ny;
         (cyl_x, cyl_y, cyl_z)
     }
+
+    /// Maps the coordinate onto a ring torus, so the noise sampled on it tiles seamlessly around
+    /// both the x and y axes.
+    ///
+    /// The ring has a circumference of 1 around its middle, like the cylinder, and the tube a
+    /// circumference of a half, so the hexes on the outer side of the ring stretch along the x
+    /// axis and the ones on the inner side shrink.
+    pub fn to_torus(&self, width: i32, height: i32) -> (f32, f32, f32) {
+        let angle_x = 2.0 * std::f32::consts::PI * self.x as f32 / width as f32;
+        let angle_y = 2.0 * std::f32::consts::PI * self.y as f32 / height as f32;
+        let ring = 1.0 / (2.0 * std::f32::consts::PI);
+        let tube = ring / 2.0;
+        let radius = ring + tube * angle_y.cos();
+        (
+            radius * angle_x.cos(),
+            radius * angle_x.sin(),
+            tube * angle_y.sin(),
+        )
+    }
 }
 
 pub struct Hex {
@@ -118,12 +150,13 @@ pub struct Hex {
 /// Creating a `Hex` and accessing its fields:
 ///
 /// ```
+/// use hextergen::cmd::Wrapping;
 /// use hextergen::mesh::{Coord, Hex};
 /// use hexx::{Hex as Hexx,HexLayout, OffsetHexMode};
 ///
 /// let layout = HexLayout::default();
 /// let offset_mode = OffsetHexMode::OddColumns;
-/// let hex = Hex::new(Coord::new(5, 5), &layout, offset_mode, 10, 10);
+/// let hex = Hex::new(Coord::new(5, 5), &layout, offset_mode, 10, 10, Wrapping::Cylinder);
 ///
 /// assert_eq!(hex.axial.y, 3, "Axial coordinates do not match");
 /// assert_eq!(hex.offset.x, 5, "Offset x-coordinate does not match");
@@ -135,14 +168,15 @@ impl Hex {
     /// Creates a new `Hex` based on its offset coordinates, layout, offset mode, and the dimensions of the map.
     ///
     /// The `offset` parameter represents the hex's position in a grid, `layout` and `offset_mode` determine how
-    /// hexagons are laid out and converted between coordinate systems, and `map_width` and `map_height`
-    /// define the boundaries for neighbor calculation.
+    /// hexagons are laid out and converted between coordinate systems, and `map_width`, `map_height` and
+    /// `wrapping` define the boundaries for neighbor calculation.
     pub fn new(
         offset: Coord,
         layout: &HexLayout,
         offset_mode: OffsetHexMode,
         map_width: i32,
         map_height: i32,
+        wrapping: Wrapping,
     ) -> Self {
         let axial = Hexx::from_offset_coordinates(offset.to_array(), offset_mode);
         let center = layout.hex_to_world_pos(axial);
@@ -153,15 +187,8 @@ impl Hex {
             .filter_map(|&n_axial| {
                 let n_offset = n_axial.to_offset_coordinates(offset_mode);
                 let n_coord = Coord::from_array(n_offset);
-                if n_coord.y >= 0 && n_coord.y < map_height {
-                    if n_coord.x < 0 || n_coord.x >= map_width {
-                        Some((n_coord.wrap_x(map_width), true))
-                    } else {
-                        Some((n_coord, false))
-                    }
-                } else {
-                    None
-                }
+                let wrapped = n_coord.wrap(map_width, map_height, wrapping)?;
+                Some((wrapped, wrapped != n_coord))
             })
             .collect();
 
@@ -175,6 +202,18 @@ impl Hex {
     }
 }
 
+// Shortest offset between two points, going across the edges of the map along the axes it wraps
+// around, which have a period greater than 0
+pub(crate) fn unwrap(mut offset: Vec2, period: Vec2) -> Vec2 {
+    if period.x > 0.0 {
+        offset.x -= (offset.x / period.x).round() * period.x;
+    }
+    if period.y > 0.0 {
+        offset.y -= (offset.y / period.y).round() * period.y;
+    }
+    offset
+}
+
 type Hexes = Array2<Hex>;
 
 /// A trait for creating a grid of `Hex` structs, representing a hexagonal grid system.
@@ -193,6 +232,7 @@ trait HexesBuilder {
     /// - `map_height`: The height of the grid, in hexes.
     /// - `layout`: The layout parameters for the hexes, defining their size and orientation.
     /// - `offset_mode`: The offset mode (even or odd) that affects the calculation of hex positions.
+    /// - `wrapping`: The axes the grid wraps around.
     ///
     /// # Returns
     /// A 2D array (`ndarray::Array2<Hex>`) representing the hexagonal grid.
@@ -201,6 +241,7 @@ trait HexesBuilder {
         map_height: i32,
         layout: &HexLayout,
         offset_mode: OffsetHexMode,
+        wrapping: Wrapping,
     ) -> (Hexes, HashMap<Hexx, Coord>);
 }
 
@@ -211,6 +252,7 @@ impl HexesBuilder for Hexes {
         map_height: i32,
         layout: &HexLayout,
         offset_mode: OffsetHexMode,
+        wrapping: Wrapping,
     ) -> (Hexes, HashMap<Hexx, Coord>) {
         let mut axial_map = HashMap::new();
         let builder: Vec<Hex> = (0..map_width)
@@ -218,7 +260,7 @@ impl HexesBuilder for Hexes {
             .flat_map(|x| {
                 (0..map_height).into_par_iter().map(move |y| {
                     let offset = Coord::new(x, y);
-                    Hex::new(offset, layout, offset_mode, map_width, map_height)
+                    Hex::new(offset, layout, offset_mode, map_width, map_height, wrapping)
                 })
             })
             .collect();
@@ -238,6 +280,7 @@ impl HexesBuilder for Hexes {
 pub struct Screen {
     pub displacement: Vec2,
     pub resolution: Vec2,
+    pub period: Vec2,
 }
 
 /// Represents the screen space required to display a hexagonal grid.
@@ -250,6 +293,8 @@ pub struct Screen {
 /// # Fields
 /// - `displacement`: A `Vec2` representing the minimum x and y offsets required to render the entire grid positively.
 /// - `resolution`: A `Vec2` representing the total width and height required to display the grid.
+/// - `period`: A `Vec2` representing the distance after which the grid repeats along each axis it wraps around,
+///   and 0 along the axes it doesn't wrap around.
 impl Screen {
     /// Constructs a new `Screen` that determines the minimum screen space needed to display a hexagonal grid.
     ///
@@ -261,10 +306,20 @@ impl Screen {
     /// - `map_width`: The width of the grid, in hexes.
     /// - `map_height`: The height of the grid, in hexes.
     /// - `hexes`: A reference to the `Hexes` representing the hexagonal grid.
+    /// - `layout`: The layout parameters for the hexes, defining their size and orientation.
+    /// - `offset_mode`: The offset mode (even or odd) that affects the calculation of hex positions.
+    /// - `wrapping`: The axes the grid wraps around.
     ///
     /// # Returns
-    /// A `Screen` instance with calculated displacement and resolution for the grid.
-    fn new(map_width: i32, map_height: i32, hexes: &Hexes) -> Self {
+    /// A `Screen` instance with calculated displacement, resolution and period for the grid.
+    fn new(
+        map_width: i32,
+        map_height: i32,
+        hexes: &Hexes,
+        layout: &HexLayout,
+        offset_mode: OffsetHexMode,
+        wrapping: Wrapping,
+    ) -> Self {
         let mut min_x = f32::INFINITY;
         let mut min_y = f32::INFINITY;
         let mut max_x = f32::NEG_INFINITY;
@@ -290,7 +345,7 @@ impl Screen {
             for corner in hex.corners.iter() {
                 update_bounds(corner.x, corner.y);
             }
-            for (n_key, wrapping) in hex.neighbors.iter().filter_map(|n| Some(n)) {
+            for (n_key, wrapping) in hex.neighbors.iter() {
                 if !wrapping {
                     let neighbor_hex = &hexes[n_key.to_dim()];
                     for corner in neighbor_hex.corners.iter() {
@@ -309,9 +364,28 @@ impl Screen {
             &mut update_bounds,
         );
 
+        // Distance between a hex and its copy one map away along each wrapped axis
+        let origin = layout.hex_to_world_pos(Hexx::from_offset_coordinates([0, 0], offset_mode));
+        let copy = |offset: [i32; 2]| {
+            let center =
+                layout.hex_to_world_pos(Hexx::from_offset_coordinates(offset, offset_mode));
+            (center - origin).abs()
+        };
+        let period = Vec2::new(
+            match wrapping.wraps_x() {
+                true => copy([map_width, 0]).x,
+                false => 0.0,
+            },
+            match wrapping.wraps_y() {
+                true => copy([0, map_height]).y,
+                false => 0.0,
+            },
+        );
+
         Self {
             displacement: Vec2::new(min_x.abs(), min_y.abs()),
             resolution: Vec2::new(max_x, max_y),
+            period,
         }
     }
 }
@@ -319,9 +393,12 @@ impl Screen {
 pub struct Mesh {
     pub hexes: Hexes,
     pub axial_map: HashMap<Hexx, Coord>,
+    pub layout: HexLayout,
+    pub offset_mode: OffsetHexMode,
     pub screen: Screen,
     pub width: i32,
     pub height: i32,
+    pub wrapping: Wrapping,
 }
 
 /// Represents the entire hexagonal grid system.
@@ -332,9 +409,13 @@ pub struct Mesh {
 ///
 /// # Fields
 /// - `hexes`: The hexagonal grid, represented as a 2D array of `Hex` structs.
+/// - `axial_map`: The offset coordinates of each hex, indexed by its axial coordinates.
+/// - `layout`: The layout used to place the hexes in 2D space.
+/// - `offset_mode`: The offset mode used to convert between axial and offset coordinates.
 /// - `screen`: The calculated screen space required to display the grid, including displacement and resolution.
 /// - `width`: The width of the grid, in hexes.
 /// - `height`: The height of the grid, in hexes.
+/// - `wrapping`: The axes the grid wraps around.
 ///
 /// # Examples
 ///
@@ -379,21 +460,257 @@ impl Mesh {
             ..Default::default()
         };
         let offset_mode = options.offset_mode();
-        let (hexes, axial_map) = Hexes::new_hexes(width, height, &layout, offset_mode);
-        let screen = Screen::new(width, height, &hexes);
+        let wrapping = options.wrapping;
+        // The offset columns or rows only line up across the wrapped edges when their count is
+        // even, otherwise the hexes on each side of the edge are half a hex apart
+        let flat = options.orientation == HexOrientation::Flat;
+        if flat && wrapping.wraps_x() && width % 2 != 0 {
+            warn!(
+                "Wrapping an odd width around the x axis with flat hexes, the columns won't line up across the edge: {}",
+                width
+            );
+        }
+        if !flat && wrapping.wraps_y() && height % 2 != 0 {
+            warn!(
+                "Wrapping an odd height around the y axis with pointy hexes, the rows won't line up across the edge: {}",
+                height
+            );
+        }
+        let (hexes, axial_map) = Hexes::new_hexes(width, height, &layout, offset_mode, wrapping);
+        let screen = Screen::new(width, height, &hexes, &layout, offset_mode, wrapping);
 
         Self {
             hexes,
             axial_map,
+            layout,
+            offset_mode,
             screen,
             width,
             height,
+            wrapping,
         }
     }
 
-    // TODO set a trait for mesh manipulation
     pub fn get_hex(&self, x: i32, y: i32) -> &Hex {
         let coord = Coord::new(x, y);
         &self.hexes[coord.to_dim()]
     }
+
+    /// Returns the coordinates of the hex containing a point in 2D space.
+    ///
+    /// The point wraps around the axes the map wraps around, and `None` is returned when it falls
+    /// off an edge of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hextergen::cmd::GenerateOptions;
+    /// use hextergen::mesh::{Coord, Mesh};
+    ///
+    /// let options = GenerateOptions {
+    ///     width: 10,
+    ///     height: 10,
+    ///     ..Default::default()
+    /// };
+    /// let mesh = Mesh::new(&options);
+    ///
+    /// let center = mesh.get_hex(3, 4).center;
+    /// assert_eq!(mesh.hex_at(center), Some(Coord::new(3, 4)));
+    ///
+    /// let step = mesh.get_hex(1, 4).center - mesh.get_hex(0, 4).center;
+    /// assert_eq!(mesh.hex_at(center + step * 10.0), Some(Coord::new(3, 4)));
+    /// assert_eq!(mesh.hex_at(center + step * 9.0), Some(Coord::new(2, 4)));
+    /// ```
+    pub fn hex_at(&self, point: Vec2) -> Option<Coord> {
+        let axial = self.layout.world_pos_to_hex(point);
+        let coord = Coord::from_array(axial.to_offset_coordinates(self.offset_mode));
+        self.wrap(coord)
+    }
+
+    /// Returns the coordinates of a hex once wrapped around the axes the map wraps around, or
+    /// `None` when it falls off an edge of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, Wrapping};
+    /// use hextergen::mesh::{Coord, Mesh};
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 10,
+    ///     height: 10,
+    ///     ..Default::default()
+    /// };
+    /// let mesh = Mesh::new(&options);
+    /// assert_eq!(mesh.wrap(Coord::new(-1, 4)), Some(Coord::new(9, 4)));
+    /// assert_eq!(mesh.wrap(Coord::new(3, 10)), None);
+    ///
+    /// options.wrapping = Wrapping::Torus;
+    /// let torus = Mesh::new(&options);
+    /// assert_eq!(torus.wrap(Coord::new(3, 10)), Some(Coord::new(3, 0)));
+    /// assert_eq!(torus.get_hex(3, 0).neighbors.len(), 6);
+    ///
+    /// options.wrapping = Wrapping::Flat;
+    /// let flat = Mesh::new(&options);
+    /// assert_eq!(flat.wrap(Coord::new(-1, 4)), None);
+    /// assert!(flat.get_hex(0, 4).neighbors.len() < 6);
+    /// ```
+    pub fn wrap(&self, coord: Coord) -> Option<Coord> {
+        coord.wrap(self.width, self.height, self.wrapping)
+    }
+}
+
+/// The cells of a mesh and how they connect to each other.
+///
+/// It is shared by the flat mesh, whose cells are the hexes of the grid, and by the hex sphere, so
+/// the same algorithms can walk both.
+pub trait Topology {
+    /// The identifier of a cell.
+    type Cell: Copy + Eq + Hash;
+
+    /// The identifiers of all the cells.
+    fn cells(&self) -> Vec<Self::Cell>;
+
+    /// The cells sharing a side with a cell.
+    fn neighbors(&self, cell: Self::Cell) -> Vec<Self::Cell>;
+
+    /// The distance between the centers of two cells, in steps between the centers of neighbor
+    /// cells.
+    fn distance(&self, from: Self::Cell, to: Self::Cell) -> f32;
+
+    /// The latitude and longitude of the center of a cell, in degrees.
+    fn coordinates(&self, cell: Self::Cell) -> (f32, f32);
+}
+
+/// Walks a topology outwards from some cells, and returns the number of steps from each cell it
+/// reaches to the closest of them.
+///
+/// The sources are at 0 steps. The walk only goes through the cells accepted by `passable`, and
+/// stops `reach` steps away from the sources.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::cmd::GenerateOptions;
+/// use hextergen::mesh::{sphere::Sphere, steps, Coord, Mesh, Topology};
+///
+/// let options = GenerateOptions {
+///     width: 10,
+///     height: 10,
+///     ..Default::default()
+/// };
+/// let mesh = Mesh::new(&options);
+/// let origin = Coord::new(4, 4);
+/// let reached = steps(&mesh, [origin], 2, |_| true);
+/// assert_eq!(reached[&origin], 0);
+/// assert_eq!(reached.len(), 1 + 6 + 12);
+/// for neighbor in mesh.neighbors(origin) {
+///     assert_eq!(reached[&neighbor], 1);
+/// }
+///
+/// // The same walk goes around the whole globe, but not through the cells left out
+/// let sphere = Sphere::new(4);
+/// let reached = steps(&sphere, [0], u32::MAX, |_| true);
+/// assert_eq!(reached.len(), sphere.cells.len());
+/// let reached = steps(&sphere, [0], u32::MAX, |cell| !sphere.cells[0].neighbors.contains(cell));
+/// assert_eq!(reached.len(), 1);
+/// ```
+pub fn steps<T: Topology>(
+    topology: &T,
+    sources: impl IntoIterator<Item = T::Cell>,
+    reach: u32,
+    passable: impl Fn(&T::Cell) -> bool,
+) -> HashMap<T::Cell, u32> {
+    let sources = sources.into_iter().map(|cell| (cell, ()));
+    nearest(topology, sources, reach, passable)
+        .into_iter()
+        .map(|(cell, (distance, ()))| (cell, distance))
+        .collect()
+}
+
+/// Walks a topology outwards from some cells like [`steps`], and returns along with the number of
+/// steps the value carried by the closest source.
+///
+/// When several sources are as close to a cell, the first of them wins.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::cmd::GenerateOptions;
+/// use hextergen::mesh::{nearest, Coord, Mesh};
+///
+/// let options = GenerateOptions {
+///     width: 10,
+///     height: 10,
+///     ..Default::default()
+/// };
+/// let mesh = Mesh::new(&options);
+/// let sources = [(Coord::new(1, 4), 'a'), (Coord::new(7, 4), 'b')];
+/// let reached = nearest(&mesh, sources, u32::MAX, |_| true);
+/// assert_eq!(reached[&Coord::new(2, 4)], (1, 'a'));
+/// assert_eq!(reached[&Coord::new(6, 4)], (1, 'b'));
+/// assert_eq!(reached[&Coord::new(4, 4)].0, 3);
+/// ```
+pub fn nearest<T: Topology, V: Copy>(
+    topology: &T,
+    sources: impl IntoIterator<Item = (T::Cell, V)>,
+    reach: u32,
+    passable: impl Fn(&T::Cell) -> bool,
+) -> HashMap<T::Cell, (u32, V)> {
+    let mut reached = HashMap::new();
+    let mut queue = VecDeque::new();
+    for (cell, value) in sources {
+        if !reached.contains_key(&cell) {
+            reached.insert(cell, (0, value));
+            queue.push_back(cell);
+        }
+    }
+
+    while let Some(cell) = queue.pop_front() {
+        let (distance, value) = reached[&cell];
+        if distance + 1 > reach {
+            continue;
+        }
+        for neighbor in topology.neighbors(cell) {
+            if passable(&neighbor) && !reached.contains_key(&neighbor) {
+                reached.insert(neighbor, (distance + 1, value));
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    reached
+}
+
+impl Topology for Mesh {
+    type Cell = Coord;
+
+    fn cells(&self) -> Vec<Coord> {
+        self.hexes.iter().map(|hex| hex.offset).collect()
+    }
+
+    fn neighbors(&self, cell: Coord) -> Vec<Coord> {
+        let hex = self.get_hex(cell.x, cell.y);
+        hex.neighbors
+            .iter()
+            .map(|(n_coord, _wrapping)| *n_coord)
+            .collect()
+    }
+
+    // Distance between the centers, the shortest way across the wrapped edges
+    fn distance(&self, from: Coord, to: Coord) -> f32 {
+        let offset = self.get_hex(to.x, to.y).center - self.get_hex(from.x, from.y).center;
+        let offset = unwrap(offset, self.screen.period);
+        let step = self.layout.hex_to_world_pos(Hexx::new(1, 0))
+            - self.layout.hex_to_world_pos(Hexx::ZERO);
+        offset.length() / step.length()
+    }
+
+    // The rows run from the north pole down to the south pole, with the equator halfway, and the
+    // columns go around the globe from the west
+    fn coordinates(&self, cell: Coord) -> (f32, f32) {
+        let latitude = 90.0 - 180.0 * (cell.y as f32 + 0.5) / self.height as f32;
+        let longitude = 360.0 * (cell.x as f32 + 0.5) / self.width as f32 - 180.0;
+        (latitude, longitude)
+    }
 }
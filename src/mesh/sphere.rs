@@ -0,0 +1,313 @@
+use super::Topology;
+use hashbrown::HashMap;
+use hexx::{Quat, Vec3};
+use rayon::prelude::*;
+
+// Golden ratio, placing the vertices of the icosahedron
+const PHI: f32 = 1.618_034;
+
+// Vertices and faces of the icosahedron, the faces winding counterclockwise seen from outside
+const VERTICES: [[f32; 3]; 12] = [
+    [-1.0, PHI, 0.0],
+    [1.0, PHI, 0.0],
+    [-1.0, -PHI, 0.0],
+    [1.0, -PHI, 0.0],
+    [0.0, -1.0, PHI],
+    [0.0, 1.0, PHI],
+    [0.0, -1.0, -PHI],
+    [0.0, 1.0, -PHI],
+    [PHI, 0.0, -1.0],
+    [PHI, 0.0, 1.0],
+    [-PHI, 0.0, -1.0],
+    [-PHI, 0.0, 1.0],
+];
+const FACES: [[usize; 3]; 20] = [
+    [0, 11, 5],
+    [0, 5, 1],
+    [0, 1, 7],
+    [0, 7, 10],
+    [0, 10, 11],
+    [1, 5, 9],
+    [5, 11, 4],
+    [11, 10, 2],
+    [10, 7, 6],
+    [7, 1, 8],
+    [3, 9, 4],
+    [3, 4, 2],
+    [3, 2, 6],
+    [3, 6, 8],
+    [3, 8, 9],
+    [4, 9, 5],
+    [2, 4, 11],
+    [6, 2, 10],
+    [8, 6, 7],
+    [9, 8, 1],
+];
+
+/// A cell of the hex sphere, a hexagon or one of the twelve pentagons.
+///
+/// # Fields
+/// - `center`: The center of the cell, on the unit sphere.
+/// - `corners`: The corners of the cell, on the unit sphere, counterclockwise seen from outside.
+/// - `neighbors`: The indexes of the cells sharing a side with the cell, in the same order as the
+///   corners, so the side from each corner to the next one faces the neighbor of the same index.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::mesh::sphere::Sphere;
+///
+/// let sphere = Sphere::new(4);
+/// let close = |a: hexx::Vec3, b: hexx::Vec3| a.distance(b) < 1e-5;
+/// for (index, cell) in sphere.cells.iter().enumerate() {
+///     assert_eq!(cell.corners.len(), cell.neighbors.len());
+///     let sides = cell.corners.len();
+///     for (i, neighbor) in cell.neighbors.iter().enumerate() {
+///         let neighbor = &sphere.cells[*neighbor];
+///
+///         // Each neighbor lists the cell back
+///         assert!(neighbor.neighbors.contains(&index));
+///
+///         // The side from the corner to the next one is shared with the neighbor
+///         let (start, end) = (cell.corners[i], cell.corners[(i + 1) % sides]);
+///         assert!(neighbor.corners.iter().any(|corner| close(*corner, start)));
+///         assert!(neighbor.corners.iter().any(|corner| close(*corner, end)));
+///
+///         // The corners turn counterclockwise seen from outside
+///         let next = cell.corners[(i + 2) % sides];
+///         assert!((end - start).cross(next - end).dot(cell.center) > 0.0);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Cell {
+    pub center: Vec3,
+    pub corners: Vec<Vec3>,
+    pub neighbors: Vec<usize>,
+}
+
+impl Cell {
+    /// Whether the cell is one of the twelve pentagons, at the vertices of the icosahedron.
+    pub fn is_pentagon(&self) -> bool {
+        self.neighbors.len() == 5
+    }
+}
+
+/// A globe of hexagons, the Goldberg polyhedron dual to a subdivided icosahedron.
+///
+/// Each face of the icosahedron is cut into a triangular grid of `frequency` steps per side and
+/// projected onto the unit sphere. The vertices of the grid are the centers of the cells, so the
+/// sphere has `10 * frequency² + 2` cells: hexagons everywhere but at the twelve vertices of the
+/// icosahedron, which become pentagons. The cells keep about the same size across the globe, the
+/// ones around the pentagons being up to a quarter narrower, and nothing wraps or ends at the
+/// poles, which sit on two of the pentagons, on the z axis.
+///
+/// # Fields
+/// - `cells`: The cells of the sphere, the pentagons first.
+/// - `frequency`: The number of steps each side of the icosahedron is cut into.
+/// - `spacing`: The mean angle between the centers of two neighbor cells, in radians.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::mesh::{sphere::Sphere, Topology};
+///
+/// let sphere = Sphere::new(8);
+/// assert_eq!(sphere.cells.len(), 10 * 8 * 8 + 2);
+/// assert_eq!(sphere.cells.iter().filter(|cell| cell.is_pentagon()).count(), 12);
+///
+/// // The north pole is on a pentagon
+/// let pole = sphere.cell_at(hexx::Vec3::Z);
+/// assert!(sphere.cells[pole].is_pentagon());
+/// assert!(sphere.coordinates(pole).0 > 89.9);
+///
+/// for neighbor in sphere.neighbors(pole) {
+///     assert!((sphere.distance(pole, neighbor) - 1.0).abs() < 0.3);
+/// }
+/// ```
+pub struct Sphere {
+    pub cells: Vec<Cell>,
+    pub frequency: u32,
+    pub spacing: f32,
+}
+
+impl Sphere {
+    // Subdivide the faces of the icosahedron into triangles, merging the vertices they share on
+    // the edges of the faces, and build each cell around a vertex from the centers of the
+    // triangles touching it
+    pub fn new(frequency: u32) -> Self {
+        let n = frequency.max(1);
+        let rotation = Quat::from_rotation_arc(Vec3::from(VERTICES[0]).normalize(), Vec3::Z);
+        let vertices: Vec<Vec3> = VERTICES
+            .iter()
+            .map(|vertex| rotation * Vec3::from(*vertex).normalize())
+            .collect();
+
+        // A point of a face is keyed by the icosahedron vertices it is made of and their weights,
+        // so the points on the edges of the faces are shared with the neighbor faces
+        let mut ids: HashMap<Vec<(usize, u32)>, usize> = HashMap::new();
+        let mut centers = Vec::new();
+        let mut triangles = Vec::new();
+        let mut point = |weights: [(usize, u32); 3]| {
+            let mut key: Vec<(usize, u32)> = weights
+                .into_iter()
+                .filter(|(_, weight)| *weight > 0)
+                .collect();
+            key.sort_unstable();
+            *ids.entry(key).or_insert_with_key(|key| {
+                let position = key.iter().fold(Vec3::ZERO, |sum, (vertex, weight)| {
+                    sum + vertices[*vertex] * *weight as f32
+                });
+                centers.push(position.normalize());
+                centers.len() - 1
+            })
+        };
+
+        // The vertices of the icosahedron come first, so the pentagons are the first cells
+        (0..VERTICES.len()).for_each(|vertex| {
+            point([(vertex, n), (vertex, 0), (vertex, 0)]);
+        });
+        for [a, b, c] in FACES {
+            let mut grid = vec![vec![0; (n + 1) as usize]; (n + 1) as usize];
+            for i in 0..=n {
+                for j in 0..=n - i {
+                    grid[i as usize][j as usize] = point([(a, n - i - j), (b, i), (c, j)]);
+                }
+            }
+            for i in 0..n as usize {
+                for j in 0..n as usize - i {
+                    triangles.push([grid[i][j], grid[i + 1][j], grid[i][j + 1]]);
+                    if i + j + 1 < n as usize {
+                        triangles.push([grid[i + 1][j], grid[i + 1][j + 1], grid[i][j + 1]]);
+                    }
+                }
+            }
+        }
+
+        let mut around = vec![Vec::new(); centers.len()];
+        triangles.iter().for_each(|triangle| {
+            triangle
+                .iter()
+                .for_each(|vertex| around[*vertex].push(*triangle))
+        });
+
+        let cells: Vec<Cell> = around
+            .into_par_iter()
+            .enumerate()
+            .map(|(index, triangles)| {
+                let center = centers[index];
+                let east = center.any_orthonormal_vector();
+                let north = center.cross(east);
+                let angle = |point: Vec3| {
+                    (point - center)
+                        .dot(north)
+                        .atan2((point - center).dot(east))
+                };
+
+                let mut corners: Vec<Vec3> = triangles
+                    .iter()
+                    .map(|triangle| {
+                        triangle
+                            .iter()
+                            .map(|vertex| centers[*vertex])
+                            .sum::<Vec3>()
+                            .normalize()
+                    })
+                    .collect();
+                corners.sort_by(|a, b| angle(*a).total_cmp(&angle(*b)));
+
+                let mut neighbors: Vec<usize> = triangles
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .filter(|vertex| *vertex != index)
+                    .collect();
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                // Each neighbor lies between the two corners of the side it shares with the cell
+                let first = angle(corners[0]);
+                let after_first =
+                    |point: Vec3| (angle(point) - first).rem_euclid(std::f32::consts::TAU);
+                neighbors
+                    .sort_by(|a, b| after_first(centers[*a]).total_cmp(&after_first(centers[*b])));
+
+                Cell {
+                    center,
+                    corners,
+                    neighbors,
+                }
+            })
+            .collect();
+
+        let (sum, count) = cells.iter().fold((0.0, 0), |(sum, count), cell| {
+            cell.neighbors
+                .iter()
+                .fold((sum, count), |(sum, count), neighbor| {
+                    (
+                        sum + cell.center.angle_between(cells[*neighbor].center),
+                        count + 1,
+                    )
+                })
+        });
+        let spacing = sum / count.max(1) as f32;
+
+        Self {
+            cells,
+            frequency: n,
+            spacing,
+        }
+    }
+
+    /// Returns the index of the cell containing a point, given as a direction from the center of
+    /// the sphere.
+    ///
+    /// The cells are walked from the first one towards the point, moving to the neighbor closest
+    /// to it until none is closer, which ends on the cell whose center is the closest to the point.
+    pub fn cell_at(&self, point: Vec3) -> usize {
+        let point = point.normalize_or_zero();
+        let mut current = 0;
+        loop {
+            let closest = self.cells[current]
+                .neighbors
+                .iter()
+                .copied()
+                .max_by(|a, b| {
+                    let a = self.cells[*a].center.dot(point);
+                    let b = self.cells[*b].center.dot(point);
+                    a.total_cmp(&b)
+                })
+                .filter(|neighbor| {
+                    self.cells[*neighbor].center.dot(point) > self.cells[current].center.dot(point)
+                });
+            match closest {
+                Some(neighbor) => current = neighbor,
+                None => return current,
+            }
+        }
+    }
+}
+
+impl Topology for Sphere {
+    type Cell = usize;
+
+    fn cells(&self) -> Vec<usize> {
+        (0..self.cells.len()).collect()
+    }
+
+    fn neighbors(&self, cell: usize) -> Vec<usize> {
+        self.cells[cell].neighbors.clone()
+    }
+
+    // Angle between the centers, over the mean angle between neighbor cells
+    fn distance(&self, from: usize, to: usize) -> f32 {
+        let angle = self.cells[from].center.angle_between(self.cells[to].center);
+        angle / self.spacing
+    }
+
+    fn coordinates(&self, cell: usize) -> (f32, f32) {
+        let center = self.cells[cell].center;
+        let latitude = center.z.clamp(-1.0, 1.0).asin().to_degrees();
+        let longitude = center.y.atan2(center.x).to_degrees();
+        (latitude, longitude)
+    }
+}
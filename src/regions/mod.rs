@@ -0,0 +1,204 @@
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{steps, Coord, Mesh},
+    topography::Topography,
+};
+use log::debug;
+use ndarray::Array2;
+
+/// The kind of a region of the map.
+///
+/// - `Continent`: A landmass covering at least the continent share of the map.
+/// - `Island`: A smaller landmass.
+/// - `Ocean`: A body of water covering at least the ocean share of the map.
+/// - `InlandSea`: A smaller body of water, enclosed by the land.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegionKind {
+    Continent,
+    Island,
+    Ocean,
+    InlandSea,
+}
+
+impl RegionKind {
+    /// Whether the regions of this kind are land.
+    pub fn is_land(&self) -> bool {
+        matches!(self, RegionKind::Continent | RegionKind::Island)
+    }
+}
+
+/// A group of connected land or water hexes.
+///
+/// # Fields
+/// - `id`: The identifier of the region, from 1.
+/// - `kind`: Whether the region is a continent, an island, an ocean or an inland sea.
+/// - `hexes`: The hexes of the region.
+/// - `coastline`: The hexes of the region touching another region, land touching water or water
+///   touching land.
+/// - `perimeter`: The number of hex sides the region shares with other regions. The sides on the
+///   edges of the map don't count.
+#[derive(Debug)]
+pub struct Region {
+    pub id: u32,
+    pub kind: RegionKind,
+    pub hexes: Vec<Coord>,
+    pub coastline: Vec<Coord>,
+    pub perimeter: usize,
+}
+
+impl Region {
+    /// The number of hexes of the region.
+    pub fn area(&self) -> usize {
+        self.hexes.len()
+    }
+}
+
+/// The continents, islands, oceans and inland seas of the map.
+///
+/// The regions are numbered from the largest to the smallest, land before water, and regions of
+/// the same area by the position of their first hex, so the same map always gets the same
+/// identifiers.
+///
+/// # Fields
+/// - `list`: The regions, sorted by their identifier.
+/// - `map`: The identifier of the region of each hex.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::cmd::GenerateOptions;
+/// use hextergen::mesh::{Coord, Mesh};
+/// use hextergen::regions::Regions;
+/// use hextergen::topography::Topography;
+///
+/// let options = GenerateOptions {
+///     width: 40,
+///     height: 30,
+///     ..Default::default()
+/// };
+/// let mesh = Mesh::new(&options);
+/// let topography = Topography::new(&options, &mesh);
+/// let regions = Regions::new(&options, &mesh, &topography);
+///
+/// // Every hex belongs to a region
+/// let area: usize = regions.list.iter().map(|region| region.area()).sum();
+/// assert_eq!(area, 40 * 30);
+///
+/// let coord = Coord::new(12, 20);
+/// let region = regions.at(&coord);
+/// assert!(region.hexes.contains(&coord));
+/// assert_eq!(region.kind.is_land(), topography.is_land(coord.x, coord.y));
+/// assert_eq!(regions.get(region.id).map(|found| found.id), Some(region.id));
+/// ```
+pub struct Regions {
+    pub list: Vec<Region>,
+    pub map: Array2<u32>,
+}
+
+impl Regions {
+    // Flood the land and the water over the neighbors of the hexes, across the wrapped edges of
+    // the map, and classify each group of connected hexes by its area
+    pub fn new(options: &GenerateOptions, mesh: &Mesh, topography: &Topography) -> Self {
+        let dim = topography.elevations.dim();
+        let total = (dim.0 * dim.1) as f32;
+        let is_land = |coord: &Coord| topography.is_land(coord.x, coord.y);
+
+        let mut visited = Array2::from_elem(dim, false);
+        let mut groups = Vec::new();
+        // Scan the rows first, so the first hex of each group is its top left one
+        for y in 0..dim.1 {
+            for x in 0..dim.0 {
+                let start = Coord::new(x as i32, y as i32);
+                if visited[start.to_dim()] {
+                    continue;
+                }
+
+                let land = is_land(&start);
+                let reached = steps(mesh, [start], u32::MAX, |coord| is_land(coord) == land);
+                let mut hexes: Vec<Coord> = reached.into_keys().collect();
+                hexes.sort_by_key(|coord| (coord.y, coord.x));
+                hexes.iter().for_each(|coord| visited[coord.to_dim()] = true);
+                groups.push((land, start, hexes));
+            }
+        }
+
+        groups.sort_by(|(a_land, a_start, a_hexes), (b_land, b_start, b_hexes)| {
+            b_land
+                .cmp(a_land)
+                .then_with(|| b_hexes.len().cmp(&a_hexes.len()))
+                .then_with(|| (a_start.y, a_start.x).cmp(&(b_start.y, b_start.x)))
+        });
+
+        let mut map = Array2::zeros(dim);
+        for (index, (_land, _start, hexes)) in groups.iter().enumerate() {
+            hexes
+                .iter()
+                .for_each(|coord| map[coord.to_dim()] = index as u32 + 1);
+        }
+
+        let list: Vec<Region> = groups
+            .into_iter()
+            .enumerate()
+            .map(|(index, (land, _start, hexes))| {
+                let id = index as u32 + 1;
+                let share = hexes.len() as f32 / total;
+                let kind = match land {
+                    true if share >= options.regions.continent => RegionKind::Continent,
+                    true => RegionKind::Island,
+                    false if share >= options.regions.ocean => RegionKind::Ocean,
+                    false => RegionKind::InlandSea,
+                };
+
+                let mut coastline = Vec::new();
+                let mut perimeter = 0;
+                for coord in &hexes {
+                    let sides = mesh
+                        .get_hex(coord.x, coord.y)
+                        .neighbors
+                        .iter()
+                        .filter(|(n_coord, _wrapping)| map[n_coord.to_dim()] != id)
+                        .count();
+                    if sides > 0 {
+                        coastline.push(*coord);
+                        perimeter += sides;
+                    }
+                }
+
+                Region {
+                    id,
+                    kind,
+                    hexes,
+                    coastline,
+                    perimeter,
+                }
+            })
+            .collect();
+
+        let count = |kind: RegionKind| list.iter().filter(|region| region.kind == kind).count();
+        debug!(
+            "Continents: {}, islands: {}, oceans: {}, inland seas: {}",
+            count(RegionKind::Continent),
+            count(RegionKind::Island),
+            count(RegionKind::Ocean),
+            count(RegionKind::InlandSea)
+        );
+
+        Self { list, map }
+    }
+
+    /// The region with the given identifier.
+    pub fn get(&self, id: u32) -> Option<&Region> {
+        let index = id.checked_sub(1)?;
+        self.list.get(index as usize)
+    }
+
+    /// The region a hex belongs to.
+    pub fn at(&self, coord: &Coord) -> &Region {
+        &self.list[self.map[coord.to_dim()] as usize - 1]
+    }
+
+    /// The regions of a kind, from the largest to the smallest.
+    pub fn of_kind(&self, kind: RegionKind) -> impl Iterator<Item = &Region> {
+        self.list.iter().filter(move |region| region.kind == kind)
+    }
+}
@@ -1,3 +1,6 @@
+use crate::climate;
+use crate::cryosphere;
+use crate::topography::InteractionVariant;
 use image::Rgba;
 
 pub trait Colors {
@@ -42,6 +45,7 @@ impl Colors for Debug {
 }
 
 impl Debug {
+    // Elevations are normalized between -1 and 1, with the sea level at 0
     pub fn from_elevation(elevation: &f32) -> Rgba<u8> {
         match elevation > &0.0 {
             true => {
@@ -61,3 +65,262 @@ impl Debug {
         }
     }
 }
+
+// Colors of the biomes
+pub enum Biome {
+    DeepOcean,
+    Ocean,
+    Beach,
+    Ice,
+    Tundra,
+    Taiga,
+    TemperateForest,
+    Grassland,
+    Desert,
+    Savanna,
+    Rainforest,
+}
+
+impl Colors for Biome {
+    fn values(&self) -> [u8; 3] {
+        match self {
+            Self::DeepOcean => [38, 72, 120],
+            Self::Ocean => [64, 112, 168],
+            Self::Beach => [230, 214, 160],
+            Self::Ice => [240, 246, 250],
+            Self::Tundra => [158, 168, 150],
+            Self::Taiga => [72, 110, 84],
+            Self::TemperateForest => [76, 140, 70],
+            Self::Grassland => [164, 190, 98],
+            Self::Desert => [222, 196, 130],
+            Self::Savanna => [196, 180, 92],
+            Self::Rainforest => [28, 104, 48],
+        }
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
+
+impl Biome {
+    pub fn from_biome(biome: &climate::Biome) -> Rgba<u8> {
+        match biome {
+            climate::Biome::DeepOcean => Biome::DeepOcean.rgba(),
+            climate::Biome::Ocean => Biome::Ocean.rgba(),
+            climate::Biome::Beach => Biome::Beach.rgba(),
+            climate::Biome::Ice => Biome::Ice.rgba(),
+            climate::Biome::Tundra => Biome::Tundra.rgba(),
+            climate::Biome::Taiga => Biome::Taiga.rgba(),
+            climate::Biome::TemperateForest => Biome::TemperateForest.rgba(),
+            climate::Biome::Grassland => Biome::Grassland.rgba(),
+            climate::Biome::Desert => Biome::Desert.rgba(),
+            climate::Biome::Savanna => Biome::Savanna.rgba(),
+            climate::Biome::Rainforest => Biome::Rainforest.rgba(),
+        }
+    }
+}
+
+// Colors of the interactions between tectonic plates, matching the legend in `view/`
+pub enum Interaction {
+    Overriding,
+    Subducting,
+    Collision,
+    IslandArc,
+    Rift,
+    Ridge,
+    Transform,
+    Pole,
+}
+
+impl Colors for Interaction {
+    fn values(&self) -> [u8; 3] {
+        match self {
+            Self::Overriding => [176, 53, 48],
+            Self::Subducting => [56, 168, 64],
+            Self::Collision => [180, 162, 45],
+            Self::IslandArc => [224, 123, 57],
+            Self::Rift => [45, 144, 180],
+            Self::Ridge => [93, 84, 196],
+            Self::Transform => [166, 58, 132],
+            Self::Pole => [150, 160, 172],
+        }
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
+
+impl Interaction {
+    pub fn from_variant(variant: &InteractionVariant) -> Rgba<u8> {
+        match variant {
+            InteractionVariant::Overriding => Interaction::Overriding.rgba(),
+            InteractionVariant::Subducting => Interaction::Subducting.rgba(),
+            InteractionVariant::Collision => Interaction::Collision.rgba(),
+            InteractionVariant::IslandArc => Interaction::IslandArc.rgba(),
+            InteractionVariant::Rift => Interaction::Rift.rgba(),
+            InteractionVariant::Ridge => Interaction::Ridge.rgba(),
+            InteractionVariant::Transform => Interaction::Transform.rgba(),
+            InteractionVariant::Pole => Interaction::Pole.rgba(),
+        }
+    }
+}
+
+// Colors of the inland water, matching the legend in `view/`
+pub enum Water {
+    River,
+    Lake,
+    SaltFlat,
+}
+
+impl Colors for Water {
+    fn values(&self) -> [u8; 3] {
+        match self {
+            Self::River => [82, 163, 255],
+            Self::Lake => [46, 111, 204],
+            Self::SaltFlat => [232, 226, 208],
+        }
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
+
+// Colors of the ice, matching the legend in `view/`
+pub enum Ice {
+    Sheet,
+    Pack,
+    Seasonal,
+}
+
+impl Colors for Ice {
+    fn values(&self) -> [u8; 3] {
+        match self {
+            Self::Sheet => [244, 248, 252],
+            Self::Pack => [214, 230, 242],
+            Self::Seasonal => [180, 206, 228],
+        }
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
+
+impl Ice {
+    pub fn from_ice(ice: &cryosphere::Ice) -> Rgba<u8> {
+        match ice {
+            cryosphere::Ice::Sheet => Ice::Sheet.rgba(),
+            cryosphere::Ice::Pack => Ice::Pack.rgba(),
+            cryosphere::Ice::Seasonal => Ice::Seasonal.rgba(),
+        }
+    }
+}
+
+// Colors of the ocean currents, and of the marks showing their direction
+pub enum Current {
+    Warm,
+    Cold,
+    Flow,
+}
+
+impl Colors for Current {
+    fn values(&self) -> [u8; 3] {
+        match self {
+            Self::Warm => [214, 96, 77],
+            Self::Cold => [67, 147, 195],
+            Self::Flow => [24, 40, 72],
+        }
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
+
+impl Current {
+    pub fn from_current(current: &climate::Current) -> Rgba<u8> {
+        match current {
+            climate::Current::Warm => Current::Warm.rgba(),
+            climate::Current::Cold => Current::Cold.rgba(),
+        }
+    }
+}
+
+// Colors of the labeled areas, like the drainage basins and the regions, picked by stepping
+// around the hue circle by the golden ratio so areas with close labels get distant hues
+pub struct Label(pub u32);
+
+impl Colors for Label {
+    fn values(&self) -> [u8; 3] {
+        let (saturation, value) = (0.55, 0.9);
+        let hue = (self.0 as f32 * 0.618_034).fract() * 6.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        [
+            ((r + m) * 255.0) as u8,
+            ((g + m) * 255.0) as u8,
+            ((b + m) * 255.0) as u8,
+        ]
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
+
+// Colors of the temperatures, from blue at -30 degrees to white at 10 and red at 30
+pub struct Temperature(pub f32);
+
+impl Colors for Temperature {
+    fn values(&self) -> [u8; 3] {
+        let (cold, mild, hot) = (
+            [49.0, 54.0, 149.0],
+            [247.0, 247.0, 247.0],
+            [165.0, 0.0, 38.0],
+        );
+        let (from, to, t) = match self.0 < 10.0 {
+            true => (cold, mild, ((self.0 + 30.0) / 40.0).clamp(0.0, 1.0)),
+            false => (mild, hot, ((self.0 - 10.0) / 20.0).clamp(0.0, 1.0)),
+        };
+        [0, 1, 2].map(|i| (from[i] + (to[i] - from[i]) * t) as u8)
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
+
+// Colors of the precipitation, from sand without rain to green at 1000 millimeters and blue at
+// 2000
+pub struct Precipitation(pub f32);
+
+impl Colors for Precipitation {
+    fn values(&self) -> [u8; 3] {
+        let (dry, wet, soaked) = (
+            [235.0, 214.0, 160.0],
+            [90.0, 170.0, 80.0],
+            [40.0, 80.0, 170.0],
+        );
+        let (from, to, t) = match self.0 < 1000.0 {
+            true => (dry, wet, (self.0 / 1000.0).clamp(0.0, 1.0)),
+            false => (wet, soaked, ((self.0 - 1000.0) / 1000.0).clamp(0.0, 1.0)),
+        };
+        [0, 1, 2].map(|i| (from[i] + (to[i] - from[i]) * t) as u8)
+    }
+
+    fn rgba(&self) -> Rgba<u8> {
+        self.alpha(255)
+    }
+}
@@ -1,17 +1,24 @@
 mod colors;
+use crate::climate::Climate;
+use crate::cmd::Layer;
+use crate::cryosphere::Cryosphere;
+use crate::hydrology::Hydrology;
 use crate::mesh::{Coord, Hex, Mesh, Screen};
-use crate::topography::{InteractionVariant, Topography};
+use crate::regions::Regions;
+use crate::topography::Topography;
 use hexx::Vec2;
 use image::{ImageBuffer, Rgba};
-use imageproc::drawing::{draw_filled_circle_mut, draw_line_segment_mut, draw_polygon_mut};
+use imageproc::drawing::{draw_line_segment_mut, draw_polygon_mut};
 use imageproc::point::Point;
 use rayon::prelude::*;
 
 use self::colors::Colors;
 
+// Number of hexes between two marks of the direction of the currents, along each axis
+const CURRENT_SPACING: i32 = 3;
+
 #[derive(Debug)]
 struct Polygon {
-    center: Point<f32>,
     corners: [Point<f32>; 6],
     color: Rgba<u8>,
 }
@@ -21,7 +28,6 @@ impl Polygon {
         let convert_point =
             |point: &Vec2| Point::new(point.x + displacement.x, point.y + displacement.y);
 
-        let center = convert_point(&hex.center);
         let corners = [
             convert_point(&hex.corners[0]),
             convert_point(&hex.corners[1]),
@@ -31,11 +37,7 @@ impl Polygon {
             convert_point(&hex.corners[5]),
         ];
 
-        Self {
-            center,
-            corners,
-            color,
-        }
+        Self { corners, color }
     }
 
     fn corners(&self) -> Vec<Point<i32>> {
@@ -44,14 +46,44 @@ impl Polygon {
             .map(|point| Point::new(point.x as i32, point.y as i32))
             .collect()
     }
-
-    fn center(&self) -> Point<i32> {
-        Point::new(self.center.x as i32, self.center.y as i32)
-    }
 }
 
 type Polygons = Vec<Polygon>;
 
+// Color of a hex in the debug layer: the elevation, covered by the terrain features, the lakes,
+// the ice, the plate seeds and the plate interactions
+fn debug_color(
+    coord: &Coord,
+    topography: &Topography,
+    cryosphere: &Cryosphere,
+    hydrology: &Hydrology,
+) -> Rgba<u8> {
+    let mut color = colors::Debug::from_elevation(topography.get_hex(coord.x, coord.y));
+    let p_coord = topography.plates.map.get(coord).unwrap();
+    if topography.features.is_hotspot(coord) {
+        color = colors::Debug::Red.rgba();
+    } else if topography.features.is_trail(coord) {
+        color = colors::Debug::Yellow.rgba();
+    } else if let Some(lake) = hydrology.lake_map[coord.to_dim()] {
+        color = match hydrology.lakes[lake].endorheic {
+            true => colors::Water::SaltFlat.rgba(),
+            false => colors::Water::Lake.rgba(),
+        };
+    } else if let Some(ice) = &cryosphere.ice[coord.to_dim()] {
+        color = colors::Ice::from_ice(ice);
+    } else if p_coord == coord {
+        color = colors::Debug::Green.rgba();
+    } else {
+        let plate = topography.plates.regions.get(p_coord).unwrap();
+        plate.border.iter().for_each(|(_n_coord, interaction)| {
+            if interaction.segment.contains(coord) {
+                color = colors::Interaction::from_variant(&interaction.variant);
+            }
+        });
+    }
+    color
+}
+
 enum Quadrant {
     TopLeft,
     TopRight,
@@ -60,12 +92,12 @@ enum Quadrant {
 }
 
 impl Quadrant {
-    fn to_string(&self) -> String {
+    fn name(&self) -> &'static str {
         match self {
-            Self::TopLeft => "top_left".to_owned(),
-            Self::TopRight => "top_right".to_owned(),
-            Self::BottomLeft => "bottom_left".to_owned(),
-            Self::BottomRight => "bottom_right".to_owned(),
+            Self::TopLeft => "top_left",
+            Self::TopRight => "top_right",
+            Self::BottomLeft => "bottom_left",
+            Self::BottomRight => "bottom_right",
         }
     }
 
@@ -84,10 +116,10 @@ impl Quadrant {
     fn displacement(&self, center: &Vec2, screen: &Screen) -> Vec2 {
         match self {
             Quadrant::TopLeft => screen.displacement + Vec2::new(0.0, 0.0),
-            Quadrant::TopRight => screen.displacement + Vec2::new(center.x * -1.0, 0.0),
-            Quadrant::BottomLeft => Vec2::new(screen.displacement.x, center.y * -1.0),
+            Quadrant::TopRight => screen.displacement + Vec2::new(-center.x, 0.0),
+            Quadrant::BottomLeft => Vec2::new(screen.displacement.x, -center.y),
             Quadrant::BottomRight => {
-                Vec2::new(screen.displacement.x + center.x * -1.0, center.y * -1.0)
+                Vec2::new(screen.displacement.x - center.x, -center.y)
             }
         }
     }
@@ -117,74 +149,153 @@ impl Quadrant {
         }
     }
 
-    fn render(&self, mesh: &Mesh, topography: &Topography, center: &Hex) {
-        let (start, end) = self.mesh(&center.offset, mesh.width as i32, mesh.height as i32);
+    fn render(&self, map: &Map, layer: Layer, center: &Hex) {
+        let Map {
+            mesh,
+            topography,
+            climate,
+            cryosphere,
+            hydrology,
+            regions,
+        } = *map;
+        let (start, end) = self.mesh(&center.offset, mesh.width, mesh.height);
         let relative_displacement = self.displacement(&center.center, &mesh.screen);
         let resolution = self.resolution(&center.center, &mesh.screen);
         let displacement = Point::new(relative_displacement.x, relative_displacement.y);
 
-        let  polygons: Polygons = (start.x..end.x)
+        let polygons: Polygons = (start.x..end.x)
             .into_par_iter()
             .flat_map(|x| {
                 (start.y..end.y).into_par_iter().map(move |y| {
                     let coord = Coord::new(x, y);
                     let hex = mesh.get_hex(x, y);
                     let elevation = topography.get_hex(x, y);
-                    let mut color = colors::Debug::from_elevation(elevation);
-                    let p_coord = topography.plates.map.get(&coord).unwrap();
-                    if p_coord == &coord {
-                        color = colors::Debug::Green.rgba();
-                    } else {
-                        let plate = topography.plates.regions.get(p_coord).unwrap();
-                        plate.border.iter().for_each(|(_n_coord, interaction)| {
-                            if interaction.segment.contains(&coord) {
-                                match interaction.variant {
-                                    InteractionVariant::Convergent => {
-                                        color = colors::Debug::Yellow.rgba()
-                                    }
-                                    InteractionVariant::Divergent => {
-                                        color = colors::Debug::Blue.rgba()
-                                    }
-                                }
+                    let color = match layer {
+                        Layer::Debug => debug_color(&coord, topography, cryosphere, hydrology),
+                        Layer::Basins => match hydrology.basins[coord.to_dim()] {
+                            0 => colors::Debug::from_elevation(elevation),
+                            basin => colors::Label(basin).rgba(),
+                        },
+                        Layer::Temperature => {
+                            colors::Temperature(climate.temperature[coord.to_dim()]).rgba()
+                        }
+                        Layer::Precipitation => {
+                            colors::Precipitation(climate.precipitation[coord.to_dim()]).rgba()
+                        }
+                        Layer::Biomes => match &cryosphere.ice[coord.to_dim()] {
+                            Some(ice) => colors::Ice::from_ice(ice),
+                            None => colors::Biome::from_biome(&climate.biomes[coord.to_dim()]),
+                        },
+                        Layer::Currents => match &climate.currents.kind[coord.to_dim()] {
+                            Some(current) => colors::Current::from_current(current),
+                            None => colors::Debug::from_elevation(elevation),
+                        },
+                        Layer::Regions => {
+                            let region = regions.at(&coord);
+                            match region.kind.is_land() {
+                                true => colors::Label(region.id).rgba(),
+                                false => colors::Label(region.id).alpha(96),
                             }
-                        });
-                    }
+                        }
+                    };
                     Polygon::new(hex, color, &displacement)
                 })
             })
             .collect();
-
         let mut img =
             ImageBuffer::from_pixel(resolution.x as u32, resolution.y as u32, Rgba([0, 0, 0, 0]));
 
         polygons.iter().for_each(|polygon| {
             draw_polygon_mut(&mut img, &polygon.corners(), polygon.color);
-            // draw_filled_circle_mut(
-            //     &mut img,
-            //     (polygon.center.x, polygon.center.y),
-            //     2,
-            //     Rgba([0, 0, 0, 120]),
-            // );
-
-            // for i in 0..6 {
-            //     let corner_a = polygon.corners[i];
-            //     let corner_a = (corner_a.x, corner_a.y);
-
-            //     let next = (i + 1) % 6;
-            //     let corner_b = polygon.corners[next];
-            //     let corner_b = (corner_b.x, corner_b.y);
-
-            //     draw_line_segment_mut(&mut img, corner_a, corner_b, Rgba([0, 0, 0, 120]));
-            // }
         });
 
-        let file_name = format!("_debug_{}.png", self.to_string());
+        // The direction of the currents is marked on a sparse grid of hexes, from their center and
+        // longer as the current grows stronger
+        if let Layer::Currents = layer {
+            let length = mesh.layout.hex_size.max_element() * 4.0;
+            for x in (start.x..end.x).filter(|x| x % CURRENT_SPACING == 0) {
+                for y in (start.y..end.y).filter(|y| y % CURRENT_SPACING == 0) {
+                    let flow = climate.currents.flow[[x as usize, y as usize]];
+                    if flow == Vec2::ZERO {
+                        continue;
+                    }
+                    let center = mesh.get_hex(x, y).center;
+                    let tip = center + flow * length;
+                    draw_line_segment_mut(
+                        &mut img,
+                        (center.x + displacement.x, center.y + displacement.y),
+                        (tip.x + displacement.x, tip.y + displacement.y),
+                        colors::Current::Flow.rgba(),
+                    );
+                }
+            }
+        }
+
+        // Rivers are drawn over the hexes, wider as their order grows
+        // Segments crossing the wrap of the map are skipped
+        let max_length = mesh.layout.hex_size.max_element() * 2.0;
+        hydrology.rivers.iter().for_each(|river| {
+            river.hexes.windows(2).for_each(|segment| {
+                let start = mesh.get_hex(segment[0].x, segment[0].y).center;
+                let end = mesh.get_hex(segment[1].x, segment[1].y).center;
+                if start.distance(end) > max_length {
+                    return;
+                }
+                let normal = (end - start).normalize_or_zero().perp();
+                let width = river.order + 1;
+                for line in 0..width {
+                    let offset = normal * (line as f32 - (width - 1) as f32 / 2.0);
+                    let shift = |point: Vec2| {
+                        (
+                            point.x + offset.x + displacement.x,
+                            point.y + offset.y + displacement.y,
+                        )
+                    };
+                    draw_line_segment_mut(
+                        &mut img,
+                        shift(start),
+                        shift(end),
+                        colors::Water::River.rgba(),
+                    );
+                }
+            });
+        });
+
+        let layer_name = match layer {
+            Layer::Debug => "debug",
+            Layer::Basins => "basins",
+            Layer::Temperature => "temperature",
+            Layer::Precipitation => "precipitation",
+            Layer::Biomes => "biomes",
+            Layer::Currents => "currents",
+            Layer::Regions => "regions",
+        };
+        let file_name = format!("_{}_{}.png", layer_name, self.name());
         img.save(file_name).unwrap();
     }
 }
 
-pub fn quadrants(mesh: &Mesh, topography: &Topography) {
-    let center = mesh.get_hex((mesh.width / 2) as i32, (mesh.height / 2) as i32);
+/// The generated map, as drawn in the rendered images.
+///
+/// # Fields
+/// - `mesh`: The hexes of the map.
+/// - `topography`: The terrain.
+/// - `climate`: The climate.
+/// - `cryosphere`: The ice.
+/// - `hydrology`: The water flowing over the land.
+/// - `regions`: The continents, islands, oceans and inland seas.
+#[derive(Clone, Copy)]
+pub struct Map<'a> {
+    pub mesh: &'a Mesh,
+    pub topography: &'a Topography,
+    pub climate: &'a Climate,
+    pub cryosphere: &'a Cryosphere,
+    pub hydrology: &'a Hydrology,
+    pub regions: &'a Regions,
+}
+
+pub fn quadrants(map: &Map, layer: Layer) {
+    let center = map.mesh.get_hex(map.mesh.width / 2, map.mesh.height / 2);
     let quadrants = [
         Quadrant::TopLeft,
         Quadrant::TopRight,
@@ -192,6 +303,6 @@ pub fn quadrants(mesh: &Mesh, topography: &Topography) {
         Quadrant::BottomRight,
     ];
     quadrants.par_iter().for_each(|quadrant| {
-        quadrant.render(mesh, topography, center);
+        quadrant.render(map, layer, center);
     });
 }
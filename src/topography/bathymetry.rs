@@ -0,0 +1,218 @@
+use super::{plates::Plates, Elevations, InteractionVariant};
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{nearest, Coord, Mesh},
+};
+use ndarray::Array2;
+
+// Width, in hexes, of the trenches and of the mid-ocean ridges
+const TRENCH_WIDTH: f32 = 2.0;
+const RIDGE_WIDTH: f32 = 3.0;
+
+// Depth of the crest of the fastest spreading mid-ocean ridges
+const RIDGE_DEPTH: f32 = 0.25;
+
+pub trait Bathymetry {
+    /// Shape the sea floor from the distance to the coast and from the borders of the plates.
+    ///
+    /// # Examples
+    ///
+    /// A single plate holds a strip of land along the western edge of the map, and a flat sea
+    /// floor everywhere else. The water deepens over the shelf, drops down the slope and levels
+    /// out on the abyssal plain, the deepest hexes being stretched to -1.
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, SeedPlacement, Wrapping};
+    /// use hextergen::mesh::{Coord, Mesh};
+    /// use hextergen::topography::{Bathymetry, Crust, Topography};
+    /// use hexx::Vec2;
+    /// use ndarray::Array2;
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 30,
+    ///     height: 5,
+    ///     wrapping: Wrapping::Flat,
+    ///     ..Default::default()
+    /// };
+    /// options.plates.placement = SeedPlacement::List(vec![Coord::new(0, 2)]);
+    /// options.hotspots.count = 0;
+    /// let mesh = Mesh::new(&options);
+    /// let topography = Topography::new(&options, &mesh);
+    /// let land = |x: usize| x < 2;
+    /// let coast = |x: usize, y: usize| {
+    ///     let axial = mesh.get_hex(x as i32, y as i32).axial;
+    ///     let hexes = mesh.hexes.iter().filter(|hex| land(hex.offset.x as usize));
+    ///     hexes.map(|hex| hex.axial.unsigned_distance_to(axial)).min().unwrap()
+    /// };
+    /// let sea_floor = || Array2::from_shape_fn((30, 5), |(x, _)| if land(x) { 0.5 } else { 0.0 });
+    ///
+    /// let mut elevations = sea_floor();
+    /// elevations.bathymetry(&options, &mesh, &topography.plates);
+    /// let bathymetry = &options.bathymetry;
+    /// let (shelf, slope_end) = (bathymetry.shelf, bathymetry.shelf + bathymetry.slope);
+    /// for ((x, y), elevation) in elevations.indexed_iter() {
+    ///     let distance = coast(x, y);
+    ///     let depth = if land(x) {
+    ///         -0.5
+    ///     } else if distance <= shelf {
+    ///         bathymetry.shelf_depth * distance as f32 / shelf as f32 / bathymetry.abyss_depth
+    ///     } else if distance < slope_end {
+    ///         // Down the slope, between the edge of the shelf and the abyss
+    ///         assert!(*elevation < -bathymetry.shelf_depth / bathymetry.abyss_depth);
+    ///         assert!(*elevation > -1.0);
+    ///         continue;
+    ///     } else {
+    ///         1.0
+    ///     };
+    ///     assert!((elevation + depth).abs() < 1e-5, "{distance}: {elevation}");
+    /// }
+    ///
+    /// // The map is then split down the middle of the sea between two oceanic plates
+    /// let (west, east) = (Coord::new(9, 2), Coord::new(24, 2));
+    /// options.plates.placement = SeedPlacement::List(vec![west, east]);
+    /// let mut topography = Topography::new(&options, &mesh);
+    /// for (coord, seed) in topography.plates.map.iter_mut() {
+    ///     *seed = if coord.x < 18 { west } else { east };
+    /// }
+    /// for (seed, plate) in topography.plates.regions.iter_mut() {
+    ///     plate.area = mesh.hexes.iter().map(|hex| hex.offset).collect();
+    ///     plate.area.retain(|coord| (coord.x < 18) == (*seed == west));
+    ///     (plate.velocity, plate.rotation) = (Vec2::ZERO, 0.0);
+    ///     (plate.crust, plate.density) = (Crust::Oceanic, 3.0);
+    /// }
+    /// let mut shape = |velocity: Vec2, density: f32| {
+    ///     let plate = topography.plates.regions.get_mut(&west).unwrap();
+    ///     (plate.velocity, plate.density) = (velocity, density);
+    ///     topography.plates.borders(&mesh);
+    ///     let mut elevations = sea_floor();
+    ///     elevations.bathymetry(&options, &mesh, &topography.plates);
+    ///     let segment = topography.plates.regions[&west].border[&east].segment.clone();
+    ///     (elevations, segment)
+    /// };
+    ///
+    /// // The denser western plate sinks below the eastern one, down a trench along its border
+    /// // which is the deepest of the sea and shallows away from it, to the abyssal plain
+    /// let (elevations, segment) = shape(Vec2::new(5.0, 0.0), 3.05);
+    /// for coord in &segment {
+    ///     assert_eq!(elevations[coord.to_dim()], -1.0);
+    ///     let (near, far) = (coord.displace(-1, 0), coord.displace(-8, 0));
+    ///     assert!(elevations[near.to_dim()] > -1.0);
+    ///     assert!(elevations[far.to_dim()] > elevations[near.to_dim()]);
+    ///     assert!(elevations[far.to_dim()] > -0.7);
+    /// }
+    ///
+    /// // Parting, the plates spread a ridge along their border, above the abyssal plain
+    /// let (elevations, segment) = shape(Vec2::new(-5.0, 0.0), 3.0);
+    /// for coord in &segment {
+    ///     let (near, far) = (coord.displace(-1, 0), coord.displace(-8, 0));
+    ///     assert!(elevations[coord.to_dim()] > elevations[near.to_dim()]);
+    ///     assert!(elevations[near.to_dim()] > elevations[far.to_dim()]);
+    ///     assert_eq!(elevations[far.to_dim()], -1.0);
+    /// }
+    /// ```
+    fn bathymetry(&mut self, options: &GenerateOptions, mesh: &Mesh, plates: &Plates);
+}
+
+impl Bathymetry for Elevations {
+    // Shape the sea floor from the distance to the coast, and from the plate boundaries
+    // The water deepens slowly over the continental shelf, drops down the continental slope and
+    // levels out on the abyssal plain, keeping a share of its former relief. The subducting plates
+    // then sink trenches along their borders, and the spreading oceanic plates raise ridges,
+    // both growing with the speed of the plates. The depths are finally stretched back so the
+    // deepest hex is at -1
+    fn bathymetry(&mut self, options: &GenerateOptions, mesh: &Mesh, plates: &Plates) {
+        let bathymetry = &options.bathymetry;
+        let water = self.mapv(|elevation| elevation <= 0.0);
+        let coasts: Vec<(Coord, f32)> = self
+            .indexed_iter()
+            .filter(|(_, elevation)| **elevation > 0.0)
+            .map(|((x, y), _)| (Coord::new(x as i32, y as i32), 1.0))
+            .collect();
+        let distances = spread(mesh, &water, coasts, f32::MAX);
+
+        let shelf_end = bathymetry.shelf as f32;
+        let slope_end = shelf_end + bathymetry.slope as f32;
+        for ((x, y), elevation) in self.indexed_iter_mut() {
+            let Some((distance, _strength)) = distances[[x, y]].filter(|_| water[[x, y]]) else {
+                continue;
+            };
+            let depth = if distance <= shelf_end {
+                bathymetry.shelf_depth * distance / shelf_end.max(1.0)
+            } else if distance < slope_end {
+                let t = (distance - shelf_end) / (slope_end - shelf_end);
+                let t = t * t * (3.0 - 2.0 * t);
+                bathymetry.shelf_depth + (bathymetry.abyss_depth - bathymetry.shelf_depth) * t
+            } else {
+                bathymetry.abyss_depth
+            };
+            *elevation = (-depth + bathymetry.relief * *elevation).min(0.0);
+        }
+
+        let boundaries = |variant: InteractionVariant| -> Vec<(Coord, f32)> {
+            plates
+                .regions
+                .values()
+                .flat_map(|plate| plate.border.values())
+                .filter(|interaction| interaction.variant == variant)
+                .flat_map(|interaction| {
+                    interaction
+                        .segment
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, coord)| water[coord.to_dim()])
+                        .map(|(index, coord)| (*coord, interaction.rate(index).min(1.0)))
+                })
+                .collect()
+        };
+
+        let trenches = spread(
+            mesh,
+            &water,
+            boundaries(InteractionVariant::Subducting),
+            TRENCH_WIDTH * 2.0,
+        );
+        let ridges = spread(
+            mesh,
+            &water,
+            boundaries(InteractionVariant::Ridge),
+            RIDGE_WIDTH * 2.0,
+        );
+        for ((x, y), elevation) in self.indexed_iter_mut() {
+            if let Some((distance, strength)) = trenches[[x, y]] {
+                let falloff = (-(distance / TRENCH_WIDTH).powi(2)).exp();
+                let floor = -bathymetry.abyss_depth - (1.0 - bathymetry.abyss_depth) * strength;
+                *elevation = elevation.min(floor * falloff);
+            }
+            if let Some((distance, strength)) = ridges[[x, y]] {
+                let falloff = (-(distance / RIDGE_WIDTH).powi(2)).exp();
+                let rise = (bathymetry.abyss_depth - RIDGE_DEPTH).max(0.0) * strength * falloff;
+                *elevation = elevation.max(-bathymetry.abyss_depth + rise).min(0.0);
+            }
+        }
+
+        let deepest = self.iter().copied().fold(0.0, f32::min);
+        if deepest < 0.0 {
+            self.mapv_inplace(|elevation| match elevation < 0.0 {
+                true => elevation / -deepest,
+                false => elevation,
+            });
+        }
+    }
+}
+
+// Distance, in hexes, from each water hex to the closest source over the water, along with the
+// strength of that source
+// The sources are at 0, and it is `None` beyond the reach and out of the water
+fn spread(
+    mesh: &Mesh,
+    water: &Array2<bool>,
+    sources: Vec<(Coord, f32)>,
+    reach: f32,
+) -> Array2<Option<(f32, f32)>> {
+    let mut distances = Array2::from_elem(water.dim(), None);
+    let reached = nearest(mesh, sources, reach as u32, |coord| water[coord.to_dim()]);
+    for (coord, (distance, strength)) in reached {
+        distances[coord.to_dim()] = Some((distance as f32, strength));
+    }
+    distances
+}
@@ -0,0 +1,308 @@
+use super::plates::Plates;
+use super::Elevations;
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{unwrap, Coord, Mesh},
+};
+use hashbrown::HashMap;
+use hexx::Vec2;
+use log::debug;
+use ndarray::Array2;
+use rayon::prelude::*;
+
+// Elevation raised on the overriding plate, per world unit of relative speed and unit of time
+const UPLIFT: f32 = 0.0015;
+
+// Elevation of the crust created at the rifts
+const NEW_CRUST: f32 = -0.5;
+
+// Rigid motion of a plate since the start of the drift
+// The plate rotates around its initial seed and then translates
+struct Frame {
+    origin: Vec2,
+    translation: Vec2,
+    angle: f32,
+}
+
+impl Frame {
+    // Current position of the plate seed in the map
+    fn seed(&self) -> Vec2 {
+        self.origin + self.translation
+    }
+
+    // Position in the initial plate of a point of the map
+    fn to_plate(&self, point: Vec2, period: Vec2) -> Vec2 {
+        let offset = unwrap(point - self.seed(), period);
+        self.origin + Vec2::from_angle(-self.angle).rotate(offset)
+    }
+}
+
+// A plate while it drifts
+// Its crust is kept in the coordinates of the initial plate, along with its elevation
+struct Drifting {
+    seed: Coord,
+    frame: Frame,
+    crust: HashMap<Coord, f32>,
+    radius: f32,
+}
+
+impl Drifting {
+    // Coordinates in the crust of the plate of a hex of the map, if the plate covers it
+    fn covers(&self, center: Vec2, mesh: &Mesh) -> Option<Coord> {
+        let point = self.frame.to_plate(center, mesh.screen.period);
+        if point.distance(self.frame.origin) > self.radius {
+            return None;
+        }
+        mesh.hex_at(point)
+            .filter(|material| self.crust.contains_key(material))
+    }
+
+    // Add crust to the plate, growing its radius if needed
+    fn grow(&mut self, material: Coord, elevation: f32, mesh: &Mesh) {
+        let center = mesh.get_hex(material.x, material.y).center;
+        let distance = unwrap(center - self.frame.origin, mesh.screen.period).length();
+        self.radius = self
+            .radius
+            .max(distance + mesh.layout.hex_size.max_element());
+        self.crust.insert(material, elevation);
+    }
+}
+
+impl Plates {
+    /// Simulate the drift of the plates along their velocities.
+    ///
+    /// On each step the plates move rigidly, and every hex of the map is checked for the plates
+    /// covering it:
+    /// - No plate: the plates spread apart, so the plate that left the hex grows new crust.
+    /// - Many plates: the plates collide, the lightest rides over the others, consuming their
+    ///   crust and raising its own by the relative speed of the plates.
+    ///
+    /// Finally, the plates are rebuilt from the hexes they cover, and the elevations are replaced
+    /// by the elevation of the crust on top.
+    ///
+    /// # Examples
+    ///
+    /// The map is split down the middle between two plates over a flat terrain, and the western
+    /// plate moves a bit more than a hex while the eastern one stands still.
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, SeedPlacement, Wrapping};
+    /// use hextergen::mesh::{Coord, Mesh};
+    /// use hextergen::topography::Topography;
+    /// use hexx::Vec2;
+    /// use ndarray::Array2;
+    ///
+    /// let (west, east) = (Coord::new(2, 2), Coord::new(7, 2));
+    /// let mut options = GenerateOptions {
+    ///     width: 10,
+    ///     height: 5,
+    ///     wrapping: Wrapping::Flat,
+    ///     ..Default::default()
+    /// };
+    /// options.plates.placement = SeedPlacement::List(vec![west, east]);
+    /// options.hotspots.count = 0;
+    /// let mesh = Mesh::new(&options);
+    /// let mut drifting = GenerateOptions::default();
+    /// drifting.drift.steps = 2;
+    ///
+    /// let drift = |velocity: f32, densities: [f32; 2]| {
+    ///     let mut topography = Topography::new(&options, &mesh);
+    ///     for (coord, seed) in topography.plates.map.iter_mut() {
+    ///         *seed = if coord.x < 5 { west } else { east };
+    ///     }
+    ///     for (seed, plate) in topography.plates.regions.iter_mut() {
+    ///         plate.area = mesh.hexes.iter().map(|hex| hex.offset).collect();
+    ///         plate.area.retain(|coord| (coord.x < 5) == (*seed == west));
+    ///         let (velocity, density) = match *seed == west {
+    ///             true => (Vec2::new(velocity, 0.0), densities[0]),
+    ///             false => (Vec2::ZERO, densities[1]),
+    ///         };
+    ///         (plate.velocity, plate.rotation, plate.density) = (velocity, 0.0, density);
+    ///     }
+    ///     let mut elevations = Array2::<f32>::zeros((10, 5));
+    ///     topography.plates.drift(&drifting, &mesh, &mut elevations);
+    ///     (topography.plates, elevations)
+    /// };
+    ///
+    /// // Converging, the plates raise the crust along the collision and carry the rest untouched
+    /// let (plates, elevations) = drift(10.0, [2.7, 3.0]);
+    /// for y in 0..5 {
+    ///     assert!(elevations[[5, y]] > 0.0);
+    ///     for x in (1..5).chain(6..10) {
+    ///         assert_eq!(elevations[[x, y]], 0.0);
+    ///     }
+    /// }
+    ///
+    /// // The lighter western crust rides over the eastern one, and the other way around
+    /// assert_eq!(plates.map[&Coord::new(5, 2)], plates.map[&Coord::new(1, 2)]);
+    /// let (plates, _elevations) = drift(10.0, [3.0, 2.7]);
+    /// assert_eq!(plates.map[&Coord::new(5, 2)], east);
+    ///
+    /// // Diverging, the western plate grows new crust in the rift it leaves behind
+    /// let (plates, elevations) = drift(-10.0, [2.7, 3.0]);
+    /// for y in 0..5 {
+    ///     assert_eq!(elevations[[4, y as usize]], -0.5);
+    ///     assert_eq!(plates.map[&Coord::new(4, y)], plates.map[&Coord::new(0, y)]);
+    /// }
+    /// assert!(elevations.iter().all(|elevation| [0.0, -0.5].contains(elevation)));
+    /// ```
+    ///
+    /// The drift is deterministic, so the same seed drifts the same plates the same way.
+    ///
+    /// ```
+    /// use hextergen::cmd::GenerateOptions;
+    /// use hextergen::mesh::Mesh;
+    /// use hextergen::topography::Topography;
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 40,
+    ///     height: 30,
+    ///     ..Default::default()
+    /// };
+    /// options.plates.count = 8;
+    /// options.drift.steps = 5;
+    /// let mesh = Mesh::new(&options);
+    ///
+    /// let first = Topography::new(&options, &mesh);
+    /// let second = Topography::new(&options, &mesh);
+    /// assert_eq!(first.elevations, second.elevations);
+    /// assert_eq!(first.plates.map, second.plates.map);
+    /// ```
+    pub fn drift(&mut self, options: &GenerateOptions, mesh: &Mesh, elevations: &mut Elevations) {
+        let step_size = options.drift.step_size;
+        let period = mesh.screen.period;
+
+        let mut keys: Vec<Coord> = self.regions.keys().copied().collect();
+        keys.sort_by_key(|coord| (coord.x, coord.y));
+
+        let mut drifting: Vec<Drifting> = keys
+            .iter()
+            .map(|seed| {
+                let mut plate = Drifting {
+                    seed: *seed,
+                    frame: Frame {
+                        origin: mesh.get_hex(seed.x, seed.y).center,
+                        translation: Vec2::ZERO,
+                        angle: 0.0,
+                    },
+                    crust: HashMap::new(),
+                    radius: 0.0,
+                };
+                for hex in &self.regions[seed].area {
+                    plate.grow(*hex, elevations[hex.to_dim()], mesh);
+                }
+                plate
+            })
+            .collect();
+
+        let index: HashMap<Coord, usize> = keys.iter().enumerate().map(|(i, k)| (*k, i)).collect();
+        let mut owners = Array2::from_shape_fn(elevations.dim(), |(x, y)| {
+            index[&self.map[&Coord::new(x as i32, y as i32)]]
+        });
+        let coords: Vec<Coord> = owners
+            .indexed_iter()
+            .map(|((x, y), _)| Coord::new(x as i32, y as i32))
+            .collect();
+
+        for _ in 0..options.drift.steps {
+            for plate in drifting.iter_mut() {
+                let seed = &self.regions[&plate.seed];
+                plate.frame.translation += seed.velocity * step_size;
+                plate.frame.angle += seed.rotation * step_size;
+            }
+
+            let covers: Vec<Vec<(usize, Coord)>> = coords
+                .par_iter()
+                .map(|coord| {
+                    let center = mesh.get_hex(coord.x, coord.y).center;
+                    drifting
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, plate)| Some((i, plate.covers(center, mesh)?)))
+                        .collect()
+                })
+                .collect();
+
+            for (coord, covering) in coords.iter().zip(covers) {
+                let center = mesh.get_hex(coord.x, coord.y).center;
+                match covering.as_slice() {
+                    [] => {
+                        let owner = &mut drifting[owners[coord.to_dim()]];
+                        if let Some(material) = mesh.hex_at(owner.frame.to_plate(center, period)) {
+                            owner.grow(material, NEW_CRUST, mesh);
+                        }
+                    }
+                    [(owner, _)] => owners[coord.to_dim()] = *owner,
+                    _ => {
+                        let velocity = |i: usize| {
+                            let plate = &drifting[i];
+                            let offset = unwrap(center - plate.frame.seed(), period);
+                            self.regions[&plate.seed].velocity_at(offset)
+                        };
+                        let (winner, w_material) = *covering
+                            .iter()
+                            .min_by(|(a, _), (b, _)| {
+                                let a_density = self.regions[&drifting[*a].seed].density;
+                                let b_density = self.regions[&drifting[*b].seed].density;
+                                a_density.total_cmp(&b_density).then(a.cmp(b))
+                            })
+                            .unwrap();
+
+                        let speed = covering
+                            .iter()
+                            .map(|(loser, _)| velocity(winner).distance(velocity(*loser)))
+                            .fold(0.0, f32::max);
+                        for (loser, l_material) in &covering {
+                            if *loser != winner {
+                                drifting[*loser].crust.remove(l_material);
+                            }
+                        }
+                        if let Some(elevation) = drifting[winner].crust.get_mut(&w_material) {
+                            *elevation += UPLIFT * speed * step_size;
+                        }
+                        owners[coord.to_dim()] = winner;
+                    }
+                }
+            }
+        }
+
+        // Rebuild the plates from the hexes they cover
+        let mut areas = vec![Vec::<Coord>::new(); drifting.len()];
+        for coord in &coords {
+            let owner = owners[coord.to_dim()];
+            let plate = &drifting[owner];
+            let center = mesh.get_hex(coord.x, coord.y).center;
+            elevations[coord.to_dim()] = mesh
+                .hex_at(plate.frame.to_plate(center, period))
+                .and_then(|material| plate.crust.get(&material).copied())
+                .unwrap_or(NEW_CRUST);
+            areas[owner].push(*coord);
+        }
+
+        let mut regions = HashMap::new();
+        self.map.clear();
+        for (plate, area) in drifting.iter().zip(areas) {
+            let mut region = self.regions.remove(&plate.seed).unwrap();
+            let seed = match area.iter().min_by(|a, b| {
+                let a_distance = unwrap(mesh.get_hex(a.x, a.y).center - plate.frame.seed(), period);
+                let b_distance = unwrap(mesh.get_hex(b.x, b.y).center - plate.frame.seed(), period);
+                a_distance.length().total_cmp(&b_distance.length())
+            }) {
+                Some(seed) => *seed,
+                None => {
+                    debug!(
+                        "Plate {},{} was consumed by the drift",
+                        plate.seed.x, plate.seed.y
+                    );
+                    continue;
+                }
+            };
+            for hex in &area {
+                self.map.insert(*hex, seed);
+            }
+            region.area = area;
+            regions.insert(seed, region);
+        }
+        self.regions = regions;
+    }
+}
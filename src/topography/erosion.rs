@@ -0,0 +1,232 @@
+use super::Elevations;
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{Coord, Mesh},
+};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+
+// Hexes a droplet can flow through before it evaporates completely
+const LIFETIME: u32 = 64;
+
+// Sediment a droplet can carry per unit of slope, speed and water
+const CAPACITY: f32 = 1.0;
+
+// Lowest slope used to compute the capacity, so droplets keep carrying sediment on flat terrain
+const MIN_SLOPE: f32 = 0.005;
+
+// Speed gained by a droplet per unit of elevation dropped
+const GRAVITY: f32 = 4.0;
+
+// Share of the water evaporated on each hex
+const EVAPORATION: f32 = 0.02;
+
+// Share of the excess over the talus moved from a hex on each thermal iteration
+const THERMAL_RATE: f32 = 0.5;
+
+pub trait Erosion {
+    /// Simulate droplets of rain flowing downhill over the land, carving the slopes and leaving
+    /// the sediment where they slow down.
+    ///
+    /// # Examples
+    ///
+    /// A funnel slopes down from every side of the map to a pit in its middle.
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, Wrapping};
+    /// use hextergen::mesh::Mesh;
+    /// use hextergen::topography::Erosion;
+    /// use ndarray::Array2;
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 9,
+    ///     height: 9,
+    ///     wrapping: Wrapping::Flat,
+    ///     ..Default::default()
+    /// };
+    /// options.erosion.droplets = 50;
+    /// let mesh = Mesh::new(&options);
+    /// let center = mesh.get_hex(4, 4).axial;
+    /// let distance = Array2::from_shape_fn((9, 9), |(x, y)| {
+    ///     mesh.get_hex(x as i32, y as i32).axial.unsigned_distance_to(center) as f32
+    /// });
+    /// let floor = 0.01;
+    /// let before = distance.mapv(|distance| match distance {
+    ///     0.0 => floor,
+    ///     distance => 0.5 + 0.1 * distance,
+    /// });
+    /// let mut elevations = before.clone();
+    /// elevations.hydraulic(&options, &mesh);
+    ///
+    /// // The material moves down the slopes, into the pit
+    /// assert!(elevations[[4, 4]] > before[[4, 4]]);
+    /// let moment = |elevations: &Array2<f32>| (elevations * &distance).sum();
+    /// assert!(moment(&elevations) < moment(&before));
+    ///
+    /// // Nothing is lost on the land, and nothing is dug below the floor of the basin
+    /// assert!((elevations.sum() - before.sum()).abs() < 1e-3);
+    /// assert!(elevations.iter().all(|elevation| *elevation >= floor));
+    /// ```
+    fn hydraulic(&mut self, options: &GenerateOptions, mesh: &Mesh);
+
+    /// Let the material slide down the slopes steeper than the talus.
+    ///
+    /// # Examples
+    ///
+    /// A spike stands in the middle of a plain, and slumps until no slope is steeper than the
+    /// talus.
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, Wrapping};
+    /// use hextergen::mesh::{Mesh, Topology};
+    /// use hextergen::topography::Erosion;
+    /// use ndarray::Array2;
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 9,
+    ///     height: 9,
+    ///     wrapping: Wrapping::Flat,
+    ///     ..Default::default()
+    /// };
+    /// options.erosion.thermal_iterations = 50;
+    /// let mesh = Mesh::new(&options);
+    /// let steepest = |elevations: &Array2<f32>| {
+    ///     mesh.cells()
+    ///         .into_iter()
+    ///         .flat_map(|cell| mesh.neighbors(cell).into_iter().map(move |n| (cell, n)))
+    ///         .map(|(cell, n)| elevations[cell.to_dim()] - elevations[n.to_dim()])
+    ///         .fold(0.0, f32::max)
+    /// };
+    ///
+    /// let before = Array2::from_shape_fn((9, 9), |(x, y)| match (x, y) {
+    ///     (4, 4) => 0.8,
+    ///     _ => 0.1,
+    /// });
+    /// let mut elevations = before.clone();
+    /// elevations.thermal(&options, &mesh);
+    /// assert!(steepest(&before) > options.erosion.talus);
+    /// assert!(steepest(&elevations) <= options.erosion.talus + 1e-6);
+    /// assert!((elevations.sum() - before.sum()).abs() < 1e-3);
+    ///
+    /// // A flat field has nothing to slide
+    /// let flat = Array2::from_elem((9, 9), 0.2);
+    /// let mut elevations = flat.clone();
+    /// elevations.thermal(&options, &mesh);
+    /// assert_eq!(elevations, flat);
+    /// ```
+    fn thermal(&mut self, options: &GenerateOptions, mesh: &Mesh);
+}
+
+impl Erosion for Elevations {
+    // Simulate droplets of rain flowing downhill over the land
+    // Each droplet starts on a random land hex and moves to its lowest neighbor, eroding the hexes
+    // while it carries less sediment than its capacity, and depositing it otherwise. The capacity
+    // grows with the slope, the speed and the water of the droplet, so valleys are carved on the
+    // steep terrain and the sediment settles as fans where the terrain flattens
+    // Droplets stop on pits, which they fill with their sediment, and when they reach the sea,
+    // where they drop it along the coast
+    fn hydraulic(&mut self, options: &GenerateOptions, mesh: &Mesh) {
+        let erosion = &options.erosion;
+        let mut rng = Pcg64Mcg::seed_from_u64(options.seed.wrapping_add(2));
+
+        for _ in 0..erosion.droplets {
+            let mut coord = Coord::new(
+                rng.gen_range(0..options.width) as i32,
+                rng.gen_range(0..options.height) as i32,
+            );
+            if self[coord.to_dim()] <= 0.0 {
+                continue;
+            }
+
+            let (mut speed, mut water, mut sediment) = (1.0_f32, 1.0_f32, 0.0_f32);
+            for _ in 0..LIFETIME {
+                let height = self[coord.to_dim()];
+                let Some(lowest) = mesh
+                    .get_hex(coord.x, coord.y)
+                    .neighbors
+                    .iter()
+                    .map(|(n_coord, _wrapping)| *n_coord)
+                    .min_by(|a, b| self[a.to_dim()].total_cmp(&self[b.to_dim()]))
+                else {
+                    // A lone hex, with nowhere to flow
+                    break;
+                };
+                let drop = height - self[lowest.to_dim()];
+
+                if drop <= 0.0 {
+                    // Stop in the pit, which the sediment fills
+                    break;
+                }
+
+                let capacity = drop.max(MIN_SLOPE) * speed * water * CAPACITY;
+                if sediment > capacity {
+                    let deposit = (sediment - capacity) * erosion.deposition;
+                    self[coord.to_dim()] += deposit;
+                    sediment -= deposit;
+                } else {
+                    // Dig at most half the drop, so the hex stays above its lowest neighbor and no
+                    // pit is carved, and never below the sea level, or the coasts would retreat
+                    // with every droplet
+                    let eroded = ((capacity - sediment) * erosion.rate)
+                        .min(drop * 0.5)
+                        .min(height);
+                    self[coord.to_dim()] -= eroded;
+                    sediment += eroded;
+                }
+
+                speed = (speed * speed + drop * GRAVITY).sqrt();
+                water *= 1.0 - EVAPORATION;
+                coord = lowest;
+
+                let elevation = &mut self[coord.to_dim()];
+                if *elevation <= 0.0 {
+                    // Drop the sediment at the coast, without raising it over the sea level, and
+                    // let the sea carry the rest away
+                    *elevation += sediment.min(-*elevation);
+                    sediment = 0.0;
+                    break;
+                }
+            }
+
+            // Leave the sediment still carried where the droplet stops, so the material eroded
+            // from the land stays on it unless it reaches the sea
+            self[coord.to_dim()] += sediment;
+        }
+    }
+
+    // Let the material slide from each hex to its lower neighbors where the terrain is steeper
+    // than the talus, the steepest difference an elevation can keep with its neighbors
+    // Only a share of the excess over the talus is moved on each iteration, spread among the lower
+    // neighbors by their excess, so the stepped ridges left by the slopes are softened while the
+    // mountain ranges keep their shape
+    // The material moved on an iteration is computed from the elevations before it, so the result
+    // does not depend on the order of the hexes
+    fn thermal(&mut self, options: &GenerateOptions, mesh: &Mesh) {
+        let talus = options.erosion.talus;
+
+        for _ in 0..options.erosion.thermal_iterations {
+            let mut moved = Elevations::zeros(self.dim());
+            for ((x, y), height) in self.indexed_iter() {
+                let drops: Vec<(Coord, f32)> = mesh
+                    .get_hex(x as i32, y as i32)
+                    .neighbors
+                    .iter()
+                    .map(|(n_coord, _wrapping)| (*n_coord, height - self[n_coord.to_dim()]))
+                    .filter(|(_, drop)| *drop > talus)
+                    .collect();
+                let excess: f32 = drops.iter().map(|(_, drop)| drop - talus).sum();
+                if excess <= 0.0 {
+                    continue;
+                }
+
+                let steepest = drops.iter().map(|(_, drop)| *drop).fold(0.0, f32::max);
+                let amount = THERMAL_RATE * (steepest - talus);
+                moved[[x, y]] -= amount;
+                for (n_coord, drop) in drops {
+                    moved[n_coord.to_dim()] += amount * (drop - talus) / excess;
+                }
+            }
+            *self += &moved;
+        }
+    }
+}
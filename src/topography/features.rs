@@ -0,0 +1,405 @@
+use super::plates::{tangent, InteractionVariant, Plates};
+use super::Elevations;
+use crate::{
+    cmd::GenerateOptions,
+    mesh::{steps, unwrap, Coord, Mesh},
+};
+use hashbrown::HashSet;
+use hexx::Vec2;
+use log::debug;
+use ndarray::Array2;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use std::{cmp::Reverse, collections::VecDeque};
+
+// Elevation raised by the strongest hotspot
+const HOTSPOT_PEAK: f32 = 0.3;
+
+// Elevation kept by each volcano of a trail relative to the next younger one
+const TRAIL_DECAY: f32 = 0.75;
+
+// Time between two volcanoes of a trail, scaling how far apart the plate carries them
+const TRAIL_TIME: f32 = 4.0;
+
+// Radius, in hexes, of the active volcano and of the extinct ones
+const HOTSPOT_RADIUS: u32 = 2;
+const TRAIL_RADIUS: u32 = 1;
+
+// Distance, in hexes, within which the borders between plates keep the hotspots away
+const BORDER_MARGIN: u32 = 4;
+
+// Consecutive rejected hotspot candidates allowed before the remaining hotspots are given up
+const HOTSPOT_ATTEMPTS: u32 = 1000;
+
+/// A mantle plume rising under a plate.
+///
+/// The hotspot stays still while the plate moves over it, so it leaves a trail of older volcanoes
+/// along the plate motion, each one more eroded than the next.
+///
+/// # Fields
+/// - `coord`: The hex above the plume, where the active volcano is.
+/// - `strength`: The share of the peak elevation raised by the plume, between 0.6 and 1.
+/// - `trail`: The extinct volcanoes, from the youngest to the oldest.
+#[derive(Debug)]
+pub struct Hotspot {
+    pub coord: Coord,
+    pub strength: f32,
+    pub trail: Vec<Coord>,
+}
+
+impl Hotspot {
+    // Follow the plate motion from the hotspot, while the crust stays on the same plate
+    fn new(coord: Coord, strength: f32, length: u32, mesh: &Mesh, plates: &Plates) -> Self {
+        let p_coord = plates.map[&coord];
+        let plate = &plates.regions[&p_coord];
+        let center = mesh.get_hex(coord.x, coord.y).center;
+        let seed = mesh.get_hex(p_coord.x, p_coord.y).center;
+        let velocity = plate.velocity_at(unwrap(center - seed, mesh.screen.period));
+
+        let mut trail = Vec::new();
+        for k in 1..=length {
+            let point = center + velocity * TRAIL_TIME * k as f32;
+            match mesh.hex_at(point) {
+                Some(hex) if plates.map[&hex] == p_coord => {
+                    if hex != coord && !trail.contains(&hex) {
+                        trail.push(hex);
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        Self {
+            coord,
+            strength,
+            trail,
+        }
+    }
+
+    // Raise the active volcano and its trail
+    fn raise(&self, mesh: &Mesh, elevations: &mut Elevations) {
+        let peak = HOTSPOT_PEAK * self.strength;
+        Self::volcano(self.coord, peak, HOTSPOT_RADIUS, mesh, elevations);
+        let mut height = peak;
+        for hex in &self.trail {
+            height *= TRAIL_DECAY;
+            Self::volcano(*hex, height, TRAIL_RADIUS, mesh, elevations);
+        }
+    }
+
+    // Raise a cone of hexes around the volcano, fading towards its radius
+    fn volcano(coord: Coord, height: f32, radius: u32, mesh: &Mesh, elevations: &mut Elevations) {
+        let axial = mesh.get_hex(coord.x, coord.y).axial;
+        for hex in axial.range(radius) {
+            let falloff = 1.0 - axial.unsigned_distance_to(hex) as f32 / (radius + 1) as f32;
+            if let Some(coord) = mesh.hex_at(mesh.layout.hex_to_world_pos(hex)) {
+                elevations[coord.to_dim()] += height * falloff;
+            }
+        }
+    }
+}
+
+/// A summit standing out from the land around it.
+///
+/// # Fields
+/// - `coord`: The hex of the summit.
+/// - `elevation`: The elevation of the summit.
+/// - `prominence`: The height of the summit above the highest saddle leading to a higher summit,
+///   or above the sea level for the highest summit of a landmass.
+#[derive(Debug)]
+pub struct Peak {
+    pub coord: Coord,
+    pub elevation: f32,
+    pub prominence: f32,
+}
+
+/// A chain of high land raised along a convergent plate border.
+///
+/// # Fields
+/// - `hexes`: The hexes of the range.
+/// - `peak`: The highest hex of the range.
+/// - `orientation`: The unit direction along which the range stretches, with the y axis pointing
+///   south.
+#[derive(Debug)]
+pub struct MountainRange {
+    pub hexes: Vec<Coord>,
+    pub peak: Coord,
+    pub orientation: Vec2,
+}
+
+/// Notable terrain features, kept so they can be rendered on top of the terrain.
+///
+/// # Fields
+/// - `hotspots`: The mantle plumes and their volcanic trails.
+/// - `peaks`: The summits rising above the prominence threshold, from the highest to the lowest.
+/// - `ranges`: The mountain ranges, from the largest to the smallest.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::cmd::{GenerateOptions, PlatesOptions};
+/// use hextergen::mesh::Mesh;
+/// use hextergen::topography::Topography;
+///
+/// let options = GenerateOptions {
+///     width: 60,
+///     height: 40,
+///     plates: PlatesOptions {
+///         count: 10,
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// let mesh = Mesh::new(&options);
+/// let mut topography = Topography::new(&options, &mesh);
+/// topography.mountains(&options, &mesh);
+/// let features = &topography.features;
+///
+/// // The hotspots rise inside the plates, away from their borders
+/// assert!(!features.hotspots.is_empty());
+/// for hotspot in &features.hotspots {
+///     let plate = &topography.plates.regions[&topography.plates.map[&hotspot.coord]];
+///     let borders = plate.border.values().flat_map(|interaction| &interaction.segment);
+///     assert!(borders.copied().all(|coord| coord != hotspot.coord));
+/// }
+/// for peak in &features.peaks {
+///     assert!(peak.prominence >= options.mountains.prominence);
+///     assert!(topography.is_land(peak.coord.x, peak.coord.y));
+/// }
+/// for range in &features.ranges {
+///     assert!(range.hexes.len() >= options.mountains.size as usize);
+///     assert!(range.hexes.contains(&range.peak));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Features {
+    pub hotspots: Vec<Hotspot>,
+    pub peaks: Vec<Peak>,
+    pub ranges: Vec<MountainRange>,
+}
+
+impl Features {
+    // Place the hotspots at random hexes inside the plates and raise their volcanoes
+    // The candidates close to a border between two plates are rejected, and the remaining
+    // hotspots are given up when too many candidates in a row are, on maps with small plates
+    pub fn hotspots(
+        &mut self,
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        plates: &Plates,
+        elevations: &mut Elevations,
+    ) {
+        let mut rng = Pcg64Mcg::seed_from_u64(options.seed.wrapping_add(1));
+        let mut placed = HashSet::<Coord>::new();
+        let borders = near_borders(mesh, plates);
+        let mut rejected = 0;
+
+        while placed.len() < options.hotspots.count as usize {
+            if rejected >= HOTSPOT_ATTEMPTS {
+                debug!(
+                    "Placed {} of {} hotspots, no room left inside the plates",
+                    placed.len(),
+                    options.hotspots.count
+                );
+                break;
+            }
+            let coord = Coord::new(
+                rng.gen_range(0..options.width) as i32,
+                rng.gen_range(0..options.height) as i32,
+            );
+            if borders.contains(&coord) || !placed.insert(coord) {
+                rejected += 1;
+                continue;
+            }
+            rejected = 0;
+            let strength = rng.gen_range(0.6..=1.0);
+            let hotspot = Hotspot::new(coord, strength, options.hotspots.trail, mesh, plates);
+            hotspot.raise(mesh, elevations);
+            self.hotspots.push(hotspot);
+        }
+    }
+
+    // Find the summits rising above the prominence threshold
+    // The land is flooded from the highest hex down, each local maximum starting an island of its
+    // own. When islands meet at a saddle, the ones with the lower summits join the one with the
+    // highest summit, and their summits get their height above the saddle as their prominence.
+    // The summits of the islands left at the end are the highest of their landmass, and stand out
+    // from the sea level
+    pub fn peaks(&mut self, options: &GenerateOptions, mesh: &Mesh, elevations: &Elevations) {
+        let elevation = |coord: &Coord| elevations[coord.to_dim()];
+        let mut hexes: Vec<Coord> = elevations
+            .indexed_iter()
+            .filter(|(_, elevation)| **elevation > 0.0)
+            .map(|((x, y), _)| Coord::new(x as i32, y as i32))
+            .collect();
+        hexes.sort_by(|a, b| elevation(b).total_cmp(&elevation(a)));
+
+        let mut islands = Array2::from_elem(elevations.dim(), None);
+        let mut parents: Vec<usize> = Vec::new();
+        let mut summits: Vec<Coord> = Vec::new();
+        let mut prominences = Vec::new();
+        for coord in hexes {
+            let mut roots: Vec<usize> = mesh
+                .get_hex(coord.x, coord.y)
+                .neighbors
+                .iter()
+                .filter_map(|(n_coord, _wrapping)| islands[n_coord.to_dim()])
+                .map(|island| root(&mut parents, island))
+                .collect();
+            roots.sort_unstable();
+            roots.dedup();
+            roots.sort_by(|a, b| elevation(&summits[*b]).total_cmp(&elevation(&summits[*a])));
+
+            let island = match roots.split_first() {
+                Some((highest, lower)) => {
+                    for island in lower {
+                        let summit = summits[*island];
+                        prominences.push((summit, elevation(&summit) - elevation(&coord)));
+                        parents[*island] = *highest;
+                    }
+                    *highest
+                }
+                None => {
+                    parents.push(parents.len());
+                    summits.push(coord);
+                    summits.len() - 1
+                }
+            };
+            islands[coord.to_dim()] = Some(island);
+        }
+        for (island, summit) in summits.iter().enumerate() {
+            if parents[island] == island {
+                prominences.push((*summit, elevation(summit)));
+            }
+        }
+
+        self.peaks = prominences
+            .into_iter()
+            .filter(|(_, prominence)| *prominence >= options.mountains.prominence)
+            .map(|(coord, prominence)| Peak {
+                coord,
+                elevation: elevation(&coord),
+                prominence,
+            })
+            .collect();
+        self.peaks.sort_by(|a, b| {
+            b.elevation
+                .total_cmp(&a.elevation)
+                .then_with(|| (a.coord.y, a.coord.x).cmp(&(b.coord.y, b.coord.x)))
+        });
+    }
+
+    // Group the high land along the convergent plate borders into mountain ranges
+    // The collisions, the overriding plates and the island arcs raise mountains close to their
+    // borders, so each group of connected highland hexes within reach of these borders is a
+    // range, when it is large enough
+    pub fn ranges(
+        &mut self,
+        options: &GenerateOptions,
+        mesh: &Mesh,
+        plates: &Plates,
+        elevations: &Elevations,
+    ) {
+        let mountains = &options.mountains;
+        let dim = elevations.dim();
+        self.ranges.clear();
+        let mut land: Vec<f32> = elevations
+            .iter()
+            .copied()
+            .filter(|elevation| *elevation > 0.0)
+            .collect();
+        land.sort_unstable_by(|a, b| a.total_cmp(b));
+        let lowland =
+            ((land.len() as f32 * (1.0 - mountains.highlands)).round() as usize).min(land.len());
+        let threshold = land.get(lowland).copied().unwrap_or(f32::MAX);
+
+        let borders = plates
+            .regions
+            .values()
+            .flat_map(|plate| plate.border.values())
+            .filter(|interaction| {
+                matches!(
+                    interaction.variant,
+                    InteractionVariant::Collision
+                        | InteractionVariant::Overriding
+                        | InteractionVariant::IslandArc
+                )
+            })
+            .flat_map(|interaction| interaction.segment.iter().copied());
+        let near = steps(mesh, borders, mountains.reach, |_| true);
+
+        let high =
+            |coord: &Coord| near.contains_key(coord) && elevations[coord.to_dim()] >= threshold;
+        let mut visited = Array2::from_elem(dim, false);
+        for y in 0..dim.1 {
+            for x in 0..dim.0 {
+                let start = Coord::new(x as i32, y as i32);
+                if visited[start.to_dim()] || !high(&start) {
+                    continue;
+                }
+
+                let mut hexes = Vec::new();
+                let mut queue = VecDeque::from([start]);
+                visited[start.to_dim()] = true;
+                while let Some(coord) = queue.pop_front() {
+                    hexes.push(coord);
+                    for (n_coord, _wrapping) in &mesh.get_hex(coord.x, coord.y).neighbors {
+                        if !visited[n_coord.to_dim()] && high(n_coord) {
+                            visited[n_coord.to_dim()] = true;
+                            queue.push_back(*n_coord);
+                        }
+                    }
+                }
+                if hexes.len() < mountains.size as usize {
+                    continue;
+                }
+
+                let peak = *hexes
+                    .iter()
+                    .max_by(|a, b| elevations[a.to_dim()].total_cmp(&elevations[b.to_dim()]))
+                    .unwrap_or(&start);
+                let orientation = tangent(&hexes, mesh).unwrap_or(Vec2::X);
+                self.ranges.push(MountainRange {
+                    hexes,
+                    peak,
+                    orientation,
+                });
+            }
+        }
+        self.ranges.sort_by_key(|range| Reverse(range.hexes.len()));
+    }
+
+    /// Whether the hex is above an active hotspot.
+    pub fn is_hotspot(&self, coord: &Coord) -> bool {
+        self.hotspots.iter().any(|hotspot| hotspot.coord == *coord)
+    }
+
+    /// Whether the hex holds an extinct volcano of a hotspot trail.
+    pub fn is_trail(&self, coord: &Coord) -> bool {
+        self.hotspots
+            .iter()
+            .any(|hotspot| hotspot.trail.contains(coord))
+    }
+}
+
+// Island a hex belongs to, following the islands it joined up to the one left standing
+fn root(parents: &mut [usize], mut island: usize) -> usize {
+    while parents[island] != island {
+        parents[island] = parents[parents[island]];
+        island = parents[island];
+    }
+    island
+}
+
+// Hexes within the margin of a border between two plates, the edges of the map aside
+fn near_borders(mesh: &Mesh, plates: &Plates) -> HashSet<Coord> {
+    let borders = plates
+        .regions
+        .values()
+        .flat_map(|plate| plate.border.values())
+        .filter(|interaction| interaction.variant != InteractionVariant::Pole)
+        .flat_map(|interaction| interaction.segment.iter().copied());
+    steps(mesh, borders, BORDER_MARGIN, |_| true)
+        .into_keys()
+        .collect()
+}
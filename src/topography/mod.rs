@@ -1,31 +1,44 @@
+mod bathymetry;
+mod drift;
+mod erosion;
+mod features;
 mod plates;
+pub mod seeds;
 
 use log::debug;
-pub use plates::InteractionVariant;
+pub use bathymetry::Bathymetry;
+pub use erosion::Erosion;
+pub use features::{Features, Hotspot, MountainRange, Peak};
+pub use plates::{Crust, InteractionVariant};
 use crate::mesh::Mesh;
-use crate::utils::noise::OctaveNoise;
-use crate::{cmd::GenerateOptions, mesh::Coord};
+use crate::{cmd::{GenerateOptions, Wrapping}, mesh::Coord, utils::noise::Noise};
 use ndarray::Array2;
 use plates::Plates;
 use rayon::prelude::*;
 
 type Elevations = Array2<f32>;
 
+// Sample a noise at a hex, on the surface the map wraps around so it tiles seamlessly
+fn sample(noise: &dyn Noise, coord: Coord, options: &GenerateOptions) -> f32 {
+    let (width, height) = (options.width as i32, options.height as i32);
+    match options.wrapping {
+        Wrapping::Torus => noise.d3(coord.to_torus(width, height)),
+        _ => noise.d3(coord.to_cilinder(width, height)),
+    }
+}
+
 trait ElevationsBuilder {
-    fn build(options: &GenerateOptions) -> Elevations;
+    fn build(options: &GenerateOptions, noise: &dyn Noise) -> Elevations;
 }
 
 impl ElevationsBuilder for Elevations {
-    fn build(options: &GenerateOptions) -> Elevations {
+    fn build(options: &GenerateOptions, noise: &dyn Noise) -> Elevations {
         let builder: Vec<f32> = (0..options.width as i32)
             .into_par_iter()
             .flat_map(|x| {
-                (0..options.height as i32).into_par_iter().map(move |y| {
-                    let coord = Coord::new(x, y);
-                    let noise = OctaveNoise::new(options.seed, 5.0, 1.0, 20, 0.6);
-
-                    noise.d3(coord.to_cilinder(options.width as i32, options.height as i32))
-                })
+                (0..options.height as i32)
+                    .into_par_iter()
+                    .map(move |y| sample(noise, Coord::new(x, y), options))
             })
             .collect();
 
@@ -34,60 +47,225 @@ impl ElevationsBuilder for Elevations {
     }
 }
 
+trait SeaLevel {
+    fn sea_level(&self, land_ratio: f32) -> f32;
+    fn normalize(&mut self, sea_level: f32);
+}
+
+impl SeaLevel for Elevations {
+    // Find the elevation leaving the requested share of hexes above it
+    // It is the highest elevation among the hexes that stay under water
+    fn sea_level(&self, land_ratio: f32) -> f32 {
+        let mut sorted: Vec<f32> = self.iter().copied().collect();
+        sorted.par_sort_unstable_by(|a, b| a.total_cmp(b));
+
+        let land = ((sorted.len() as f32 * land_ratio).round() as usize).min(sorted.len());
+        match sorted.len() - land {
+            0 => sorted[0].next_down(),
+            water => sorted[water - 1],
+        }
+    }
+
+    // Rescale the elevations so the sea level is at 0, land goes up to 1 and water down to -1
+    fn normalize(&mut self, sea_level: f32) {
+        let (min, max) = self
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), &e| (min.min(e), max.max(e)));
+        let depth = (sea_level - min).max(f32::EPSILON);
+        let height = (max - sea_level).max(f32::EPSILON);
+
+        self.par_mapv_inplace(|elevation| {
+            if elevation > sea_level {
+                (elevation - sea_level) / height
+            } else {
+                (elevation - sea_level) / depth
+            }
+        });
+    }
+}
+
+/// The terrain of the map.
+///
+/// # Fields
+/// - `elevations`: The elevation of each hex, normalized between -1 and 1 with the sea level at 0.
+///   Land hexes are above 0, up to 1 for the highest hex, and water hexes are at or below 0, down
+///   to -1 for the deepest hex. The sea level is picked to reach the requested land ratio, and
+///   picked again once the erosion has moved the coasts.
+/// - `plates`: The tectonic plates that shaped the elevations.
+/// - `features`: The notable features of the terrain, like the hotspots, the peaks and the
+///   mountain ranges. The peaks and the ranges are empty until `Topography::mountains` is called.
+///
+/// # Examples
+///
+/// The finished terrain has the requested share of land, and spans the whole range.
+///
+/// ```
+/// use hextergen::cmd::{GenerateOptions, Wrapping};
+/// use hextergen::mesh::Mesh;
+/// use hextergen::topography::Topography;
+///
+/// let mut options = GenerateOptions {
+///     width: 60,
+///     height: 40,
+///     ..Default::default()
+/// };
+/// options.plates.count = 10;
+/// for (land_ratio, wrapping) in [(0.29, Wrapping::Cylinder), (0.5, Wrapping::Flat)] {
+///     options.land_ratio = land_ratio;
+///     options.wrapping = wrapping;
+///     let mesh = Mesh::new(&options);
+///     let topography = Topography::new(&options, &mesh);
+///
+///     let land = topography.elevations.iter().filter(|e| **e > 0.0).count();
+///     assert_eq!(land, (60.0 * 40.0 * land_ratio).round() as usize);
+///     let max = topography.elevations.iter().copied().fold(f32::MIN, f32::max);
+///     let min = topography.elevations.iter().copied().fold(f32::MAX, f32::min);
+///     assert_eq!((min, max), (-1.0, 1.0));
+/// }
+/// ```
 pub struct Topography {
     pub elevations: Elevations,
     pub plates: Plates,
+    pub features: Features,
 }
 
 impl Topography {
     pub fn new(options: &GenerateOptions, mesh: &Mesh) -> Self {
-        let mut elevations = Elevations::build(options);
-        let mut plates = Plates::new(options, &mesh);
-        plates.borders(&mesh);
-        plates.slopes(&mesh);
+        let noise = &options.noise;
+        let elevation_noise = noise.elevation.build(options.seed, &noise.warp);
+        let mountains_noise = noise.mountains.build(options.seed.wrapping_add(2), &noise.warp);
+        let mut elevations = Elevations::build(options, elevation_noise.as_ref());
+        let mut plates = Plates::new(options, mesh);
+        plates.crusts(options, &elevations);
+        if options.drift.steps > 0 {
+            plates.drift(options, mesh, &mut elevations);
+        }
+        plates.borders(mesh);
+        plates.slopes(mesh);
 
+        // The mountains noise scales the uplift of the convergent borders along their length,
+        // from none to twice their rate
         plates.regions.iter().for_each(|(_p_coord, plate)| {
             plate.slopes.iter().for_each(|slope| {
+                let uplift = matches!(
+                    slope.variant,
+                    InteractionVariant::Collision
+                        | InteractionVariant::Overriding
+                        | InteractionVariant::IslandArc
+                );
                 slope.hexes.iter().enumerate().for_each(|(i, hex)| {
-                    elevations
-                        .get_mut([hex.x as usize, hex.y as usize])
-                        .map(|elevation| {
-                            *elevation =
-                                slope
-                                    .variant
-                                    .effect(i, slope.hexes.len(), elevation.clone())
-                        });
+                    if let Some(elevation) = elevations.get_mut([hex.x as usize, hex.y as usize]) {
+                        let rate = match uplift {
+                            true => {
+                                let relief = sample(mountains_noise.as_ref(), *hex, options);
+                                slope.rate * (1.0 + relief)
+                            }
+                            false => slope.rate,
+                        };
+                        *elevation = slope.variant.effect(i, slope.hexes.len(), *elevation, rate);
+                    }
                 })
             })
         });
 
-        let min_elevation =
-            elevations.iter().fold(
-                f32::MAX,
-                |acc, &elevation| {
-                    if elevation < acc {
-                        elevation
-                    } else {
-                        acc
-                    }
-                },
-            );
-        let max_elevation =
-            elevations.iter().fold(
-                f32::MIN,
-                |acc, &elevation| {
-                    if elevation > acc {
-                        elevation
-                    } else {
-                        acc
-                    }
-                },
-            );
+        let mut features = Features::default();
+        features.hotspots(options, mesh, &plates, &mut elevations);
 
-        debug!("Min elevation: {}", min_elevation);
-        debug!("Max elevation: {}", max_elevation);
+        let sea_level = elevations.sea_level(options.land_ratio);
+        elevations.normalize(sea_level);
+        debug!("Sea level: {}", sea_level);
+
+        // The erosion wears the coasts down and slides material across them, so the sea level is
+        // solved again on the eroded terrain. The bathymetry only reshapes the water, below it
+        elevations.hydraulic(options, mesh);
+        elevations.thermal(options, mesh);
+        let eroded_sea_level = elevations.sea_level(options.land_ratio);
+        elevations.normalize(eroded_sea_level);
+        debug!("Eroded sea level: {}", eroded_sea_level);
+        elevations.bathymetry(options, mesh, &plates);
+
+        Topography {
+            elevations,
+            plates,
+            features,
+        }
+    }
+
+    /// Find the peaks and the mountain ranges of the terrain.
+    ///
+    /// The ice sheets raise the land under them, so the peaks and the ranges are only searched
+    /// once the cryosphere has been generated, and the previous ones are replaced.
+    ///
+    /// # Examples
+    ///
+    /// A ridge crosses the middle row of a small island, with a summit on each side of a saddle. A
+    /// collision border runs through the saddle, raising the ridge around it into a range.
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, Wrapping};
+    /// use hextergen::mesh::{Coord, Mesh};
+    /// use hextergen::topography::{InteractionVariant, Topography};
+    /// use ndarray::Array2;
+    ///
+    /// let mut options = GenerateOptions {
+    ///     width: 9,
+    ///     height: 5,
+    ///     wrapping: Wrapping::Flat,
+    ///     ..Default::default()
+    /// };
+    /// options.plates.count = 1;
+    /// options.mountains.highlands = 0.3;
+    /// options.mountains.reach = 2;
+    /// let mesh = Mesh::new(&options);
+    /// let mut topography = Topography::new(&options, &mesh);
+    /// let ridge = [0.3, 0.5, 0.8, 0.6, 0.5, 0.55, 0.65, 0.4, 0.3];
+    /// topography.elevations = Array2::from_shape_fn((9, 5), |(x, y)| match y {
+    ///     0 | 4 => -0.5,
+    ///     2 => ridge[x],
+    ///     _ => 0.1,
+    /// });
+    /// for plate in topography.plates.regions.values_mut() {
+    ///     for interaction in plate.border.values_mut() {
+    ///         interaction.variant = InteractionVariant::Collision;
+    ///         interaction.segment = vec![Coord::new(4, 2)];
+    ///     }
+    /// }
+    /// topography.mountains(&options, &mesh);
+    /// let features = &topography.features;
+    ///
+    /// // The highest summit stands out from the sea level, the other one from the saddle
+    /// assert_eq!(features.peaks.len(), 2);
+    /// assert_eq!(features.peaks[0].coord, Coord::new(2, 2));
+    /// assert_eq!(features.peaks[0].prominence, 0.8);
+    /// assert_eq!(features.peaks[1].coord, Coord::new(6, 2));
+    /// assert!((features.peaks[1].prominence - 0.15).abs() < 1e-6);
+    ///
+    /// // The high hexes of the ridge within reach of the border make a range
+    /// assert_eq!(features.ranges.len(), 1);
+    /// let range = &features.ranges[0];
+    /// let mut hexes = range.hexes.clone();
+    /// hexes.sort_by_key(|coord| coord.x);
+    /// assert_eq!(hexes, (2..=6).map(|x| Coord::new(x, 2)).collect::<Vec<_>>());
+    /// assert_eq!(range.peak, Coord::new(2, 2));
+    ///
+    /// // A range smaller than the smallest size is left out
+    /// options.mountains.size = 6;
+    /// topography.mountains(&options, &mesh);
+    /// assert!(topography.features.ranges.is_empty());
+    /// ```
+    pub fn mountains(&mut self, options: &GenerateOptions, mesh: &Mesh) {
+        self.features.peaks(options, mesh, &self.elevations);
+        self.features.ranges(options, mesh, &self.plates, &self.elevations);
+        debug!(
+            "Peaks: {}, mountain ranges: {}",
+            self.features.peaks.len(),
+            self.features.ranges.len()
+        );
+    }
 
-        Topography { elevations, plates }
+    /// Whether the hex is above the sea level.
+    pub fn is_land(&self, x: i32, y: i32) -> bool {
+        *self.get_hex(x, y) > 0.0
     }
 
     pub fn get_hex(&self, x: i32, y: i32) -> &f32 {
@@ -1,134 +1,308 @@
 use crate::{
     cmd::GenerateOptions,
-    mesh::{Coord, Hex, Mesh},
+    mesh::{unwrap, Coord, Hex, Mesh},
     utils::queues,
 };
 use hashbrown::{HashMap, HashSet};
 use hexx::Vec2;
+use ndarray::Array2;
 use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg64Mcg;
 use rayon::prelude::*;
 use std::collections::VecDeque;
-use std::f32::consts::PI;
+use std::f32::consts::TAU;
 
-// Seeds for the tectonic plates
-type Seeds = Vec<Coord>;
+// Special seed to identify the edge of the map
+const MAP_EDGE: Coord = Coord { x: -1, y: -1 };
+
+// Plates sliding along the border faster than this ratio of their approach speed are transform
+const TRANSFORM_RATIO: f32 = 2.0;
+
+// Fastest linear speed of a plate, in world units per step
+const MAX_SPEED: f32 = 10.0;
+
+// Fastest rotation of a plate around its seed, in radians per step
+const MAX_ROTATION: f32 = 0.01;
 
-trait SeedsBuilder {
-    fn build(options: &GenerateOptions, mesh: &Mesh) -> Seeds;
+// Relative speed at which the interaction effects have their nominal strength
+const REFERENCE_SPEED: f32 = MAX_SPEED;
+
+// Cap of the interaction rate, so fast rotating plates don't blow up the elevations
+const MAX_RATE: f32 = 2.5;
+
+// Relative movement between two plates
+#[derive(Clone, Copy)]
+enum Motion {
+    Convergent,
+    Divergent,
+    Transform,
 }
 
-impl SeedsBuilder for Seeds {
-    // Generate the seeds for the tectonic plates randomly picking points in the map
-    // The points must be placed at a minimum distance from each other
-    fn build(options: &GenerateOptions, mesh: &Mesh) -> Seeds {
-        let mut seeds: Seeds = Vec::new();
-        let mut rng = Pcg64Mcg::seed_from_u64(options.seed);
-        let diagonal = (options.width.pow(2) as f32 + options.height.pow(2) as f32).sqrt();
-        let min_distance = diagonal / 75.0 * 2.5;
-
-        while seeds.len() < 75 {
-            let x = rng.gen_range(2..options.width - 2);
-            let y = rng.gen_range(2..options.height - 2);
-            let candidate_hex = mesh.get_hex(x as i32, y as i32);
-            if seeds.iter().all(|c: &Coord| {
-                mesh.get_hex(c.x, c.y)
-                    .axial
-                    .distance_to(candidate_hex.axial) as f32
-                    > min_distance
-            }) {
-                seeds.push(Coord::new(x as i32, y as i32));
-            }
+impl Motion {
+    // Categorize the movement between two plates
+    // The relative velocity is split into its components across and along the border, the
+    // plates are sliding past each other when the tangential component dominates, otherwise
+    // they converge when the plate moves towards its neighbor
+    fn new(relative: Vec2, normal: Vec2) -> Self {
+        let approach = relative.dot(normal);
+        let sliding = relative.dot(normal.perp()).abs();
+        if sliding > approach.abs() * TRANSFORM_RATIO {
+            Motion::Transform
+        } else if approach > 0.0 {
+            Motion::Convergent
+        } else {
+            Motion::Divergent
         }
+    }
 
-        seeds
+    // Calculate the unit normal of a border segment, pointing towards the neighbor plate seed
+    fn normal(segment: &[Coord], other: &Hex, mesh: &Mesh) -> Vec2 {
+        let first = mesh.get_hex(segment[0].x, segment[0].y).center;
+        let towards = unwrap(other.center - first, mesh.screen.period).normalize_or_zero();
+        match tangent(segment, mesh) {
+            Some(tangent) if tangent.perp().dot(towards) < 0.0 => -tangent.perp(),
+            Some(tangent) => tangent.perp(),
+            None => towards,
+        }
     }
-}
 
-// Special seed to identify the edge of the map
-const MAP_EDGE: Coord = Coord { x: -1, y: -1 };
+    // Calculate the unit normal of a segment on the edge of the map, pointing out of the map
+    // The segments reaching the top or bottom rows face the poles, the others face the sides of
+    // a map that doesn't wrap around the x axis
+    fn edge_normal(segment: &[Coord], mesh: &Mesh) -> Vec2 {
+        let first = mesh.get_hex(segment[0].x, segment[0].y);
+        let middle = mesh.get_hex(mesh.width / 2, mesh.height / 2);
+        let outwards = first.center - middle.center;
+        match segment
+            .iter()
+            .any(|hex| hex.y == 0 || hex.y == mesh.height - 1)
+        {
+            true => Vec2::new(0.0, outwards.y.signum()),
+            false => Vec2::new(outwards.x.signum(), 0.0),
+        }
+    }
+}
 
-// Angle that identifies the direction of the movements
-enum Angle {
-    In,
-    Out,
+/// Type of crust of a tectonic plate.
+///
+/// Oceanic crust is denser than continental crust, so it sinks below it when they converge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crust {
+    Continental,
+    Oceanic,
 }
 
-impl Angle {
-    fn from_degree(degree: f32) -> Self {
-        if degree >= 270.0 || degree <= 90.0 {
-            Angle::In
-        } else {
-            Angle::Out
+impl Crust {
+    // Typical density of the crust in g/cm³
+    fn density(&self) -> f32 {
+        match self {
+            Crust::Continental => 2.7,
+            Crust::Oceanic => 3.0,
         }
     }
 }
 
-// Categorize the angle relationship between two plates
-#[derive(Debug, Clone, Copy)]
+// Calculate the direction of a group of hexes, like a border segment, as the principal axis of
+// their centers
+// The centers are unwrapped around the first one, so groups crossing the x wrap stay whole
+pub(super) fn tangent(hexes: &[Coord], mesh: &Mesh) -> Option<Vec2> {
+    let first = hexes.first()?;
+    let first = mesh.get_hex(first.x, first.y).center;
+    let period = mesh.screen.period;
+    let offsets: Vec<Vec2> = hexes
+        .iter()
+        .map(|coord| unwrap(mesh.get_hex(coord.x, coord.y).center - first, period))
+        .collect();
+
+    let mean = offsets.iter().copied().sum::<Vec2>() / offsets.len() as f32;
+    let (xx, yy, xy) = offsets
+        .iter()
+        .fold((0.0, 0.0, 0.0), |(xx, yy, xy), offset| {
+            let d = *offset - mean;
+            (xx + d.x * d.x, yy + d.y * d.y, xy + d.x * d.y)
+        });
+    if xx + yy <= f32::EPSILON {
+        return None;
+    }
+
+    let angle = 0.5 * (2.0 * xy).atan2(xx - yy);
+    Some(Vec2::new(angle.cos(), angle.sin()))
+}
+
+/// Categorize the interaction of a plate with its neighbor, from the plate's side of the border.
+///
+/// - `Subducting`: The plate sinks below a lighter one, opening a trench.
+/// - `Overriding`: The continental plate rides over an oceanic one, raising a volcanic range.
+/// - `Collision`: Two continental plates crush into each other, raising a wide mountain range.
+/// - `IslandArc`: The lighter oceanic plate rides over a denser one, raising a chain of islands.
+/// - `Rift`: The continental plate tears apart from its neighbor, sinking a valley.
+/// - `Ridge`: The oceanic plate spreads from its neighbor, raising a mid-ocean ridge.
+/// - `Transform`: The plates slide past each other, leaving a fault line.
+/// - `Pole`: The plate reaches an edge of the map, around a pole for the top and bottom edges,
+///   leaving the terrain untouched.
+///
+/// # Examples
+///
+/// The map is split down the middle between two plates, the western one moving east into the
+/// eastern one, which stands still. The interaction on each side of the border depends on the
+/// crusts of both plates.
+///
+/// ```
+/// use hextergen::cmd::{GenerateOptions, SeedPlacement, Wrapping};
+/// use hextergen::mesh::{Coord, Mesh};
+/// use hextergen::topography::{Crust, InteractionVariant, Topography};
+/// use hexx::Vec2;
+///
+/// let (west, east) = (Coord::new(2, 2), Coord::new(7, 2));
+/// let mut options = GenerateOptions {
+///     width: 10,
+///     height: 5,
+///     wrapping: Wrapping::Flat,
+///     ..Default::default()
+/// };
+/// options.plates.placement = SeedPlacement::List(vec![west, east]);
+/// options.hotspots.count = 0;
+/// let mesh = Mesh::new(&options);
+/// let mut topography = Topography::new(&options, &mesh);
+/// for (coord, seed) in topography.plates.map.iter_mut() {
+///     *seed = if coord.x < 5 { west } else { east };
+/// }
+/// for (seed, plate) in topography.plates.regions.iter_mut() {
+///     plate.area = mesh.hexes.iter().map(|hex| hex.offset).collect();
+///     plate.area.retain(|coord| (coord.x < 5) == (*seed == west));
+/// }
+///
+/// // Interactions of the western plate with the eastern one, and back
+/// let interact = |topography: &mut Topography, velocity: Vec2, crusts: [(Crust, f32); 2]| {
+///     for (seed, plate) in topography.plates.regions.iter_mut() {
+///         let (side, (crust, density)) = match *seed == west {
+///             true => (velocity, crusts[0]),
+///             false => (Vec2::ZERO, crusts[1]),
+///         };
+///         (plate.velocity, plate.rotation) = (side, 0.0);
+///         (plate.crust, plate.density) = (crust, density);
+///     }
+///     topography.plates.borders(&mesh);
+///     let regions = &topography.plates.regions;
+///     (
+///         regions[&west].border[&east].variant,
+///         regions[&east].border[&west].variant,
+///     )
+/// };
+/// let continental = (Crust::Continental, 2.7);
+/// let oceanic = (Crust::Oceanic, 3.0);
+/// let converging = Vec2::new(5.0, 0.0);
+///
+/// use InteractionVariant::*;
+/// let pairs = [
+///     ([continental, continental], (Collision, Collision)),
+///     ([continental, oceanic], (Overriding, Subducting)),
+///     ([oceanic, continental], (Subducting, Overriding)),
+/// ];
+/// for (crusts, interactions) in pairs {
+///     assert_eq!(interact(&mut topography, converging, crusts), interactions);
+/// }
+///
+/// // Between two oceanic plates, the denser one sinks below the lighter one
+/// let heavy = (Crust::Oceanic, 3.05);
+/// let light = (Crust::Oceanic, 2.95);
+/// let interactions = interact(&mut topography, converging, [heavy, light]);
+/// assert_eq!(interactions, (Subducting, IslandArc));
+/// let interactions = interact(&mut topography, converging, [light, heavy]);
+/// assert_eq!(interactions, (IslandArc, Subducting));
+///
+/// // Moving apart, the continental crust tears into a rift and the oceanic one spreads a ridge
+/// let diverging = -converging;
+/// let interactions = interact(&mut topography, diverging, [continental, oceanic]);
+/// assert_eq!(interactions, (Rift, Ridge));
+///
+/// // Sliding along the border more than twice as fast as they approach or part, the plates are
+/// // transform whatever their crusts, and only the approach counts below that
+/// for crusts in [[continental, continental], [continental, oceanic]] {
+///     for sliding in [Vec2::new(1.0, 2.5), Vec2::new(-1.0, -2.5)] {
+///         assert_eq!(interact(&mut topography, sliding, crusts), (Transform, Transform));
+///     }
+/// }
+/// let pushing = Vec2::new(1.0, 1.5);
+/// let interactions = interact(&mut topography, pushing, [continental, continental]);
+/// assert_eq!(interactions, (Collision, Collision));
+/// let pulling = Vec2::new(-1.0, 1.5);
+/// let interactions = interact(&mut topography, pulling, [continental, continental]);
+/// assert_eq!(interactions, (Rift, Rift));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InteractionVariant {
-    Convergent,
-    Divergent,
+    Subducting,
+    Overriding,
+    Collision,
+    IslandArc,
+    Rift,
+    Ridge,
+    Transform,
+    Pole,
 }
 
 impl InteractionVariant {
-    // Categorize the interaction between two plates
-    fn new(
-        origin_center: &Hex,
-        origin_direction: f32,
-        origin_magnitude: f32,
-        other_center: &Hex,
-        other_direction: f32,
-        other_magnitude: f32,
-    ) -> Self {
-        let angle_between = Self::angle_between(&origin_center.center, &other_center.center);
-        let origin_angle = (origin_direction - angle_between).rem_euclid(360.0);
-        let other_angle = (other_direction - angle_between).rem_euclid(360.0);
-        let origin_direction = Angle::from_degree(origin_angle);
-        let other_direction = Angle::from_degree(other_angle);
-
-        use Angle::*;
-        match (origin_direction, other_direction) {
-            (In, In) => InteractionVariant::Convergent,
-            (Out, Out) => InteractionVariant::Convergent,
-            (In, Out) => {
-                if origin_magnitude > other_magnitude {
-                    InteractionVariant::Convergent
-                } else {
-                    InteractionVariant::Divergent
-                }
-            }
-            (Out, In) => {
-                if other_magnitude > origin_magnitude {
-                    InteractionVariant::Convergent
-                } else {
-                    InteractionVariant::Divergent
+    // Categorize the interaction between two plates from their movement and crusts
+    fn new(motion: Motion, origin: &Plate, other: &Plate) -> Self {
+        use Crust::*;
+        match motion {
+            Motion::Transform => InteractionVariant::Transform,
+            Motion::Divergent => Self::divergent(origin.crust),
+            Motion::Convergent => match (origin.crust, other.crust) {
+                (Continental, Continental) => InteractionVariant::Collision,
+                (Continental, Oceanic) => InteractionVariant::Overriding,
+                (Oceanic, Continental) => InteractionVariant::Subducting,
+                (Oceanic, Oceanic) => {
+                    if origin.density > other.density {
+                        InteractionVariant::Subducting
+                    } else {
+                        InteractionVariant::IslandArc
+                    }
                 }
-            }
+            },
+        }
+    }
+
+    // Interaction of a plate moving away from its neighbor
+    fn divergent(crust: Crust) -> Self {
+        match crust {
+            Crust::Continental => InteractionVariant::Rift,
+            Crust::Oceanic => InteractionVariant::Ridge,
         }
     }
 
-    // Calculate the angle between two points
-    fn angle_between(origin: &Vec2, other: &Vec2) -> f32 {
-        ((other.y - origin.y).atan2(other.x - origin.x) * 180.0 / PI).rem_euclid(360.0)
+    // Gaussian bump centered at `center` with the given `width`
+    fn bump(t: f32, center: f32, width: f32) -> f32 {
+        (-((t - center) / width).powi(2)).exp()
     }
 
-    pub fn effect(&self, index: usize, slope_len: usize, elevation: f32) -> f32 {
-        let contrast = 1.065;
-        let steepness = 0.013;
+    // Apply the interaction to the elevation of a hex of the slope
+    // The `rate` is the relative speed of the plates at the border, relative to the reference
+    // speed, so faster boundaries raise higher mountains and sink deeper rifts
+    pub fn effect(&self, index: usize, slope_len: usize, elevation: f32, rate: f32) -> f32 {
+        let contrast = 1.0 + 0.065 * rate;
+        let steepness = 0.013 * rate;
 
         if (index as f32) < (slope_len as f32) * 0.3 {
             return elevation * 0.995;
         }
 
-
         let i = index as f32;
         let n = (slope_len - 1) as f32;
+        let t = i / n;
 
+        // Elevation profile from the seed (0) to the border (1)
         let distance_effect = match self {
-            InteractionVariant::Convergent => i / n,
-            InteractionVariant::Divergent => -i / n,
+            InteractionVariant::Collision => 1.2 * t,
+            InteractionVariant::Overriding => 0.6 * t + Self::bump(t, 0.8, 0.12),
+            InteractionVariant::IslandArc => Self::bump(t, 0.85, 0.08) - 0.3 * t,
+            InteractionVariant::Subducting => -1.5 * t * t,
+            InteractionVariant::Rift => 0.4 * Self::bump(t, 0.75, 0.1) - Self::bump(t, 1.0, 0.12),
+            InteractionVariant::Ridge => 0.8 * Self::bump(t, 1.0, 0.1) - 0.3 * t,
+            InteractionVariant::Transform => -0.5 * Self::bump(t, 1.0, 0.05),
+            InteractionVariant::Pole => 0.0,
         };
 
         let adjustment = distance_effect * steepness;
@@ -142,6 +316,7 @@ impl InteractionVariant {
 #[derive(Debug)]
 pub struct Slope {
     pub variant: InteractionVariant,
+    pub rate: f32,
     pub hexes: Vec<Coord>,
 }
 
@@ -149,7 +324,7 @@ pub type Slopes = Vec<Slope>;
 
 trait SlopesBuilder {
     fn build(seed: &Coord, interaction: &Interaction, mesh: &Mesh) -> Slopes;
-    fn wrap_distance(origin: &Vec2, other: &Vec2, resolution_x: f32) -> f32;
+    fn wrap_distance(origin: &Vec2, other: &Vec2, period: Vec2) -> f32;
 }
 
 impl SlopesBuilder for Slopes {
@@ -160,7 +335,8 @@ impl SlopesBuilder for Slopes {
         interaction
             .segment
             .par_iter()
-            .map(|b_coord| {
+            .enumerate()
+            .map(|(index, b_coord)| {
                 let b_hex = mesh.get_hex(b_coord.x, b_coord.y);
                 let (x, y) = (seed.x * b_coord.x, seed.y * b_coord.y);
                 let unique_seed = ((x as u64) << 32) | (y as u64);
@@ -175,11 +351,8 @@ impl SlopesBuilder for Slopes {
 
                 while let Some(current) = queue.pop_front() {
                     let current_hex = mesh.get_hex(current.x, current.y);
-                    let current_to_border = Self::wrap_distance(
-                        &current_hex.center,
-                        &b_hex.center,
-                        mesh.screen.resolution.x,
-                    );
+                    let current_to_border =
+                        Self::wrap_distance(&current_hex.center, &b_hex.center, mesh.screen.period);
 
                     let mut neighbors = current_hex
                         .neighbors
@@ -199,7 +372,7 @@ impl SlopesBuilder for Slopes {
                         let neighbor_to_border = Self::wrap_distance(
                             &neighbor_hex.center,
                             &b_hex.center,
-                            mesh.screen.resolution.x,
+                            mesh.screen.period,
                         );
                         if neighbor_to_border <= current_to_border {
                             hexes.push(*n_coord);
@@ -212,6 +385,7 @@ impl SlopesBuilder for Slopes {
 
                 Slope {
                     variant: interaction.variant,
+                    rate: interaction.rate(index),
                     hexes,
                 }
             })
@@ -219,35 +393,129 @@ impl SlopesBuilder for Slopes {
     }
 
     // Calculate the distance between two points considering the wrapping of the map
-    fn wrap_distance(origin: &Vec2, other: &Vec2, resolution_x: f32) -> f32 {
-        let direct_distance = origin.distance(*other);
-        let wrap_distance_x =
-            (resolution_x - (origin.x - other.x).abs()).min((origin.x - other.x).abs());
-        let wrap_distance_y =
-            (resolution_x - (origin.y - other.y).abs()).min((origin.y - other.y).abs());
-
-        direct_distance.min((wrap_distance_x.powi(2) + wrap_distance_y.powi(2)).sqrt())
+    fn wrap_distance(origin: &Vec2, other: &Vec2, period: Vec2) -> f32 {
+        unwrap(*other - *origin, period).length()
     }
 }
 
 // Contains the interaction between self plate and others
 // There is a segment of hexes that represent the border between the plates
 // The variant is applied to the whole segment
+// The relative velocity of the plate to its neighbor is kept for each hex of the segment,
+// along with the normal of the border pointing towards the neighbor
 #[derive(Debug)]
 pub struct Interaction {
     pub variant: InteractionVariant,
     pub segment: Vec<Coord>,
+    pub normal: Vec2,
+    pub relative: Vec<Vec2>,
+}
+
+impl Interaction {
+    /// Relative speed of the plates at a hex of the segment, scaled by the reference speed.
+    ///
+    /// Transform borders are measured along the border, the others across it.
+    ///
+    /// # Examples
+    ///
+    /// The map is split down the middle between two continental plates, the eastern one
+    /// standing still while the western one moves or spins around its seed.
+    ///
+    /// ```
+    /// use hextergen::cmd::{GenerateOptions, SeedPlacement, Wrapping};
+    /// use hextergen::mesh::{Coord, Mesh};
+    /// use hextergen::topography::{Crust, InteractionVariant, Topography};
+    /// use hexx::Vec2;
+    ///
+    /// let (west, east) = (Coord::new(2, 2), Coord::new(7, 2));
+    /// let mut options = GenerateOptions {
+    ///     width: 10,
+    ///     height: 5,
+    ///     wrapping: Wrapping::Flat,
+    ///     ..Default::default()
+    /// };
+    /// options.plates.placement = SeedPlacement::List(vec![west, east]);
+    /// options.hotspots.count = 0;
+    /// let mesh = Mesh::new(&options);
+    /// let mut topography = Topography::new(&options, &mesh);
+    /// for (coord, seed) in topography.plates.map.iter_mut() {
+    ///     *seed = if coord.x < 5 { west } else { east };
+    /// }
+    /// for (seed, plate) in topography.plates.regions.iter_mut() {
+    ///     plate.area = mesh.hexes.iter().map(|hex| hex.offset).collect();
+    ///     plate.area.retain(|coord| (coord.x < 5) == (*seed == west));
+    ///     (plate.velocity, plate.rotation) = (Vec2::ZERO, 0.0);
+    ///     (plate.crust, plate.density) = (Crust::Continental, 2.7);
+    /// }
+    ///
+    /// let drift = |topography: &mut Topography, velocity: Vec2, rotation: f32| {
+    ///     let plate = topography.plates.regions.get_mut(&west).unwrap();
+    ///     (plate.velocity, plate.rotation) = (velocity, rotation);
+    ///     topography.plates.borders(&mesh);
+    /// };
+    /// let measure = |topography: &Topography| {
+    ///     let interaction = &topography.plates.regions[&west].border[&east];
+    ///     let rates: Vec<f32> = (0..interaction.segment.len())
+    ///         .map(|index| interaction.rate(index))
+    ///         .collect();
+    ///     (interaction.variant, rates)
+    /// };
+    ///
+    /// // Half the reference speed across the border, up to the cap
+    /// for (velocity, rate) in [(5.0, 0.5), (15.0, 1.5), (40.0, 2.5)] {
+    ///     drift(&mut topography, Vec2::new(velocity, 0.0), 0.0);
+    ///     let (variant, rates) = measure(&topography);
+    ///     assert_eq!(variant, InteractionVariant::Collision);
+    ///     assert!(rates.iter().all(|r| (r - rate).abs() < 1e-5));
+    /// }
+    ///
+    /// // A transform border is measured along the border, not across it
+    /// drift(&mut topography, Vec2::new(1.0, 5.0), 0.0);
+    /// let (variant, rates) = measure(&topography);
+    /// assert_eq!(variant, InteractionVariant::Transform);
+    /// assert!(rates.iter().all(|r| (r - 0.5).abs() < 1e-5));
+    ///
+    /// // A spinning plate moves each hex of the border its own way, faster away from its seed
+    /// drift(&mut topography, Vec2::ZERO, 0.01);
+    /// let interaction = &topography.plates.regions[&west].border[&east];
+    /// let seed = mesh.get_hex(west.x, west.y).center;
+    /// for (coord, relative) in interaction.segment.iter().zip(&interaction.relative) {
+    ///     let offset = mesh.get_hex(coord.x, coord.y).center - seed;
+    ///     assert!((*relative - offset.perp() * 0.01).length() < 1e-5);
+    /// }
+    /// let (_variant, rates) = measure(&topography);
+    /// assert!(rates.iter().any(|r| (r - rates[0]).abs() > 1e-3));
+    /// ```
+    pub fn rate(&self, index: usize) -> f32 {
+        let relative = self.relative[index];
+        let speed = match self.variant {
+            InteractionVariant::Transform => relative.dot(self.normal.perp()),
+            _ => relative.dot(self.normal),
+        };
+        (speed.abs() / REFERENCE_SPEED).min(MAX_RATE)
+    }
 }
 
 // Represents a tectonic plate
 #[derive(Debug)]
 pub struct Plate {
-    pub direction: f32,
+    pub velocity: Vec2,
+    pub rotation: f32,
+    pub crust: Crust,
+    pub density: f32,
     pub area: Vec<Coord>,
     pub border: HashMap<Coord, Interaction>,
     pub slopes: Slopes,
 }
 
+impl Plate {
+    // Velocity of the plate at an offset from its seed
+    // It combines the linear velocity with the rotation of the plate around its seed
+    pub fn velocity_at(&self, offset: Vec2) -> Vec2 {
+        self.velocity + self.rotation * offset.perp()
+    }
+}
+
 // Represents the tectonic plates
 #[derive(Debug)]
 pub struct Plates {
@@ -264,15 +532,20 @@ impl Plates {
         let mut visited = HashSet::<Coord>::new();
         let mut rng = Pcg64Mcg::seed_from_u64(options.seed);
 
-        let seeds = Seeds::build(options, mesh);
+        let seeds = options.plates.placement.strategy().place(options, mesh);
 
         for seed in seeds {
             queue.enqueue((seed, seed));
             map.insert(seed, seed);
+            let heading = rng.gen_range(0.0..TAU);
+            let speed = rng.gen_range(0.0..MAX_SPEED);
             regions.insert(
                 seed,
                 Plate {
-                    direction: rng.gen_range(0.0..360.0),
+                    velocity: Vec2::new(heading.cos(), heading.sin()) * speed,
+                    rotation: rng.gen_range(-MAX_ROTATION..MAX_ROTATION),
+                    crust: Crust::Oceanic,
+                    density: Crust::Oceanic.density(),
                     area: vec![seed],
                     border: HashMap::new(),
                     slopes: Vec::new(),
@@ -295,85 +568,133 @@ impl Plates {
         Self { regions, map }
     }
 
+    // Assign the crust type of each plate
+    // The plates with the highest mean elevation are continental, until they cover the land ratio
+    // The density of each plate varies slightly around the typical density of its crust
+    pub fn crusts(&mut self, options: &GenerateOptions, elevations: &Array2<f32>) {
+        let mut rng = Pcg64Mcg::seed_from_u64(options.seed);
+        let total = (options.width * options.height) as f32;
+
+        let mut means: Vec<(Coord, f32)> = self
+            .regions
+            .iter()
+            .map(|(p_coord, plate)| {
+                let sum: f32 = plate.area.iter().map(|hex| elevations[hex.to_dim()]).sum();
+                (*p_coord, sum / plate.area.len() as f32)
+            })
+            .collect();
+        means.sort_by(|(a_coord, a), (b_coord, b)| {
+            b.total_cmp(a)
+                .then_with(|| (a_coord.x, a_coord.y).cmp(&(b_coord.x, b_coord.y)))
+        });
+
+        let mut continental = 0.0;
+        for (p_coord, _mean) in means {
+            let plate = self.regions.get_mut(&p_coord).unwrap();
+            plate.crust = if continental < options.land_ratio * total {
+                continental += plate.area.len() as f32;
+                Crust::Continental
+            } else {
+                Crust::Oceanic
+            };
+            plate.density = plate.crust.density() + rng.gen_range(-0.1..0.1);
+        }
+    }
+
     // Identify the borders between the tectonic plates
     // Each hex in the plate's area is checked for its neighbors, if any of them
     // belongs to another plate, a border is created
     // It uses the map to check which plate the neighbor belongs to
-    // It identifies the direction of the movements between the plates
-    // And categorizes the movements into an interaction
+    // It computes the relative velocity between the plates on each border hex
+    // And categorizes the mean relative movement of the segment into an interaction
     // TODO: After writhing the responsabilities of this code, it's clear that needs some refactor to split responsibilities
     pub fn borders(&mut self, mesh: &Mesh) {
-        let directions: HashMap<Coord, f32> = self
-            .regions
-            .par_iter()
-            .map(|(p_coord, plate)| (*p_coord, plate.direction))
-            .collect();
-        let magnitudes: HashMap<Coord, f32> = self
+        let period = mesh.screen.period;
+        // Velocity of a plate at a point of the map
+        let velocity_at = |p_coord: &Coord, point: Vec2| {
+            let seed = mesh.get_hex(p_coord.x, p_coord.y).center;
+            self.regions[p_coord].velocity_at(unwrap(point - seed, period))
+        };
+
+        let borders: Vec<(Coord, HashMap<Coord, Interaction>)> = self
             .regions
             .par_iter()
-            .map(|(p_coord, plate)| (*p_coord, plate.area.len() as f32))
-            .collect();
+            .map(|(p_coord, plate)| {
+                let mut segments = HashMap::<Coord, Vec<Coord>>::new();
+
+                for hex in &plate.area {
+                    let neighbors = &mesh.get_hex(hex.x, hex.y).neighbors;
+
+                    if neighbors.len() < 6 {
+                        // Edge of the map
+                        segments.entry(MAP_EDGE).or_default().push(*hex);
+                    } else {
+                        // Regular interaction
+                        neighbors.iter().for_each(|(neighbor, _wrapping)| {
+                            let n_p_coord = self.map[neighbor];
+                            if n_p_coord != *p_coord {
+                                segments.entry(n_p_coord).or_default().push(*hex);
+                            }
+                        });
+                    }
+                }
 
-        self.regions.par_iter_mut().for_each(|(p_coord, plate)| {
-            for hex in &plate.area {
-                let neighbors = &mesh.get_hex(hex.x, hex.y).neighbors;
-
-                if neighbors.len() < 6 {
-                    // Edge of the map
-
-                    plate
-                        .border
-                        .entry(MAP_EDGE)
-                        .or_insert(Interaction {
-                            variant: InteractionVariant::Divergent,
-                            segment: Vec::new(),
-                        })
-                        .segment
-                        .push(*hex);
-                } else {
-                    // Regular interaction
-
-                    neighbors.iter().for_each(|(neighbor, _wrapping)| {
-                        let n_p_coord = self.map[neighbor];
-
-                        if n_p_coord != *p_coord {
-                            let origin_direction = directions[p_coord];
-                            let other_direction = directions[&n_p_coord];
-                            let origin_magnitude = magnitudes[p_coord];
-                            let other_magnitude = magnitudes[&n_p_coord];
-
-                            plate
-                                .border
-                                .entry(n_p_coord)
-                                .or_insert({
-                                    let slope = InteractionVariant::new(
-                                        &mesh.get_hex(hex.x, hex.y),
-                                        origin_direction,
-                                        origin_magnitude,
-                                        &mesh.get_hex(n_p_coord.x, n_p_coord.y),
-                                        other_direction,
-                                        other_magnitude,
-                                    );
-                                    Interaction {
-                                        variant: slope,
-                                        segment: Vec::new(),
-                                    }
+                let border = segments
+                    .into_iter()
+                    .map(|(n_p_coord, segment)| {
+                        let centers = segment.iter().map(|hex| mesh.get_hex(hex.x, hex.y).center);
+                        let (variant, normal, relative) = if n_p_coord == MAP_EDGE {
+                            let relative: Vec<Vec2> =
+                                centers.map(|center| velocity_at(p_coord, center)).collect();
+                            let normal = Motion::edge_normal(&segment, mesh);
+                            (InteractionVariant::Pole, normal, relative)
+                        } else {
+                            let relative: Vec<Vec2> = centers
+                                .map(|center| {
+                                    velocity_at(p_coord, center) - velocity_at(&n_p_coord, center)
                                 })
-                                .segment
-                                .push(*hex);
-                        }
-                    });
-                }
-            }
-        })
+                                .collect();
+                            let normal = Motion::normal(
+                                &segment,
+                                mesh.get_hex(n_p_coord.x, n_p_coord.y),
+                                mesh,
+                            );
+                            let mean =
+                                relative.iter().copied().sum::<Vec2>() / relative.len() as f32;
+                            let motion = Motion::new(mean, normal);
+                            let other = &self.regions[&n_p_coord];
+                            (
+                                InteractionVariant::new(motion, plate, other),
+                                normal,
+                                relative,
+                            )
+                        };
+                        let interaction = Interaction {
+                            variant,
+                            segment,
+                            normal,
+                            relative,
+                        };
+                        (n_p_coord, interaction)
+                    })
+                    .collect();
+                (*p_coord, border)
+            })
+            .collect();
+
+        for (p_coord, border) in borders {
+            self.regions.get_mut(&p_coord).unwrap().border = border;
+        }
     }
 
     // Generate the slopes between the border hex and the seed hex
+    // The edges of the map, like the poles, shape no slopes
     pub fn slopes(&mut self, mesh: &Mesh) {
         self.regions.par_iter_mut().for_each(|(p_coord, plate)| {
             plate.slopes = plate
                 .border
                 .par_iter()
+                .filter(|(_n_coord, interaction)| interaction.variant != InteractionVariant::Pole)
                 .flat_map(|(_n_coord, interaction)| Slopes::build(p_coord, interaction, mesh))
                 .collect();
         });
@@ -0,0 +1,300 @@
+use crate::{
+    cmd::{GenerateOptions, SeedPlacement},
+    mesh::{Coord, Mesh},
+};
+use hexx::Hex as Hexx;
+use log::warn;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+// Seeds for the tectonic plates
+pub type Seeds = Vec<Coord>;
+
+// Candidates tried around an active seed before it is retired in the Poisson disk sampling
+const POISSON_CANDIDATES: u32 = 30;
+
+/// A strategy to place the seeds from which the tectonic plates grow.
+///
+/// Every strategy must be deterministic for a given `options.seed`, and must return distinct
+/// coordinates inside the mesh.
+pub trait SeedStrategy {
+    fn place(&self, options: &GenerateOptions, mesh: &Mesh) -> Seeds;
+}
+
+impl SeedPlacement {
+    /// Returns the strategy implementing this placement.
+    pub fn strategy(&self) -> Box<dyn SeedStrategy + '_> {
+        match self {
+            SeedPlacement::Random => Box::new(Random),
+            SeedPlacement::Poisson => Box::new(Poisson),
+            SeedPlacement::Jittered => Box::new(Jittered),
+            SeedPlacement::List(seeds) => Box::new(List { seeds }),
+        }
+    }
+}
+
+// Minimum distance between seeds, either requested or derived from the map diagonal
+fn min_distance(options: &GenerateOptions) -> f32 {
+    let diagonal = (options.width.pow(2) as f32 + options.height.pow(2) as f32).sqrt();
+    options
+        .plates
+        .spacing
+        .unwrap_or(diagonal / options.plates.count as f32 * 2.5)
+}
+
+// Whether a candidate lies farther than the minimum distance from every seed
+// All the strategies keeping their seeds apart compare the distances the same way, so a given
+// spacing means the same whatever the strategy
+fn spaced(candidate: Coord, seeds: &[Coord], min_distance: f32, mesh: &Mesh) -> bool {
+    let axial = mesh.get_hex(candidate.x, candidate.y).axial;
+    seeds.iter().all(|seed| {
+        let distance = mesh.get_hex(seed.x, seed.y).axial.distance_to(axial);
+        distance > 0 && distance as f32 > min_distance
+    })
+}
+
+// Shrink the minimum distance once the retry budget is spent without placing every seed
+fn relax(min_distance: &mut f32, placed: usize, count: usize) {
+    *min_distance *= 0.9;
+    warn!(
+        "Placed {} of {} plate seeds, relaxing spacing to {:.2}",
+        placed, count, min_distance
+    );
+}
+
+/// Randomly picks hexes on the mesh, keeping a minimum distance between them.
+///
+/// When `options.plates.attempts` candidates in a row are rejected, the minimum distance is
+/// relaxed so the requested number of plates can always be placed.
+pub struct Random;
+
+impl SeedStrategy for Random {
+    fn place(&self, options: &GenerateOptions, mesh: &Mesh) -> Seeds {
+        let count = options.plates.count as usize;
+        let mut seeds: Seeds = Vec::with_capacity(count);
+        let mut rng = Pcg64Mcg::seed_from_u64(options.seed);
+        let mut min_distance = min_distance(options);
+
+        // Keep the seeds away from the map edges unless the map is too small for it
+        let inner = options.width.saturating_sub(4) * options.height.saturating_sub(4);
+        let margin = if inner >= options.plates.count { 2 } else { 0 };
+
+        let mut rejected = 0;
+        while seeds.len() < count {
+            let x = rng.gen_range(margin..options.width - margin) as i32;
+            let y = rng.gen_range(margin..options.height - margin) as i32;
+            let candidate = Coord::new(x, y);
+            if spaced(candidate, &seeds, min_distance, mesh) {
+                seeds.push(candidate);
+                rejected = 0;
+            } else {
+                rejected += 1;
+                if rejected >= options.plates.attempts {
+                    relax(&mut min_distance, seeds.len(), count);
+                    rejected = 0;
+                }
+            }
+        }
+
+        seeds
+    }
+}
+
+/// Bridson's Poisson disk sampling over the hex mesh.
+///
+/// New candidates are picked from the hex rings beyond the minimum distance and up to twice that
+/// distance around an active seed, and an active seed is retired once none of its candidates fits.
+/// When `options.plates.attempts` candidates in a row are rejected, or no active seed is left
+/// before the requested number of plates is reached, the minimum distance is relaxed and every
+/// seed becomes active again, so the requested number of plates can always be placed.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::cmd::{GenerateOptions, SeedPlacement};
+/// use hextergen::mesh::Mesh;
+///
+/// let mut options = GenerateOptions {
+///     width: 40,
+///     height: 30,
+///     ..Default::default()
+/// };
+/// options.plates.count = 12;
+/// options.plates.spacing = Some(4.0);
+/// let mesh = Mesh::new(&options);
+/// let distance = |a: &hextergen::mesh::Coord, b: &hextergen::mesh::Coord| {
+///     mesh.get_hex(a.x, a.y).axial.distance_to(mesh.get_hex(b.x, b.y).axial)
+/// };
+///
+/// // Both strategies keep their seeds farther apart than the spacing
+/// for placement in [SeedPlacement::Random, SeedPlacement::Poisson] {
+///     let seeds = placement.strategy().place(&options, &mesh);
+///     assert_eq!(seeds.len(), 12);
+///     for (i, a) in seeds.iter().enumerate() {
+///         assert!(seeds[i + 1..].iter().all(|b| distance(a, b) > 4));
+///     }
+/// }
+///
+/// // A spacing too wide for the map is relaxed until every plate fits
+/// options.plates.spacing = Some(100.0);
+/// options.plates.attempts = 10;
+/// let seeds = SeedPlacement::Poisson.strategy().place(&options, &mesh);
+/// assert_eq!(seeds.len(), 12);
+/// ```
+pub struct Poisson;
+
+impl SeedStrategy for Poisson {
+    fn place(&self, options: &GenerateOptions, mesh: &Mesh) -> Seeds {
+        let count = options.plates.count as usize;
+        let mut seeds: Seeds = Vec::with_capacity(count);
+        let mut active = Vec::<Coord>::new();
+        let mut rng = Pcg64Mcg::seed_from_u64(options.seed);
+        let offset_mode = options.offset_mode();
+        let mut min_distance = min_distance(options);
+
+        let first = Coord::new(
+            rng.gen_range(0..options.width) as i32,
+            rng.gen_range(0..options.height) as i32,
+        );
+        seeds.push(first);
+        active.push(first);
+
+        let mut rejected = 0;
+        while seeds.len() < count {
+            if active.is_empty() || rejected >= options.plates.attempts {
+                relax(&mut min_distance, seeds.len(), count);
+                active = seeds.clone();
+                rejected = 0;
+            }
+
+            let index = rng.gen_range(0..active.len());
+            let origin = mesh.get_hex(active[index].x, active[index].y).axial;
+            // The closest ring lying farther than the minimum distance
+            let radius = min_distance.max(0.0).floor() as u32 + 1;
+
+            let candidate = (0..POISSON_CANDIDATES).find_map(|_| {
+                let distance = rng.gen_range(radius..=radius * 2);
+                let position = rng.gen_range(0..(distance * 6) as usize);
+                let candidate = origin
+                    .ring(distance)
+                    .nth(position)
+                    .and_then(|axial| to_mesh(axial, mesh, offset_mode))
+                    .filter(|coord| spaced(*coord, &seeds, min_distance, mesh));
+                if candidate.is_none() {
+                    rejected += 1;
+                }
+                candidate
+            });
+
+            match candidate {
+                Some(coord) => {
+                    seeds.push(coord);
+                    active.push(coord);
+                    rejected = 0;
+                }
+                None => {
+                    active.swap_remove(index);
+                }
+            }
+        }
+
+        seeds
+    }
+}
+
+// Map an axial hex into the mesh, wrapping around the axes the map wraps around
+fn to_mesh(axial: Hexx, mesh: &Mesh, offset_mode: hexx::OffsetHexMode) -> Option<Coord> {
+    let [x, y] = axial.to_offset_coordinates(offset_mode);
+    mesh.wrap(Coord::new(x, y))
+}
+
+/// Splits the map into a grid with roughly one cell per plate and places each seed at a random
+/// position inside its cell.
+///
+/// When the grid has more cells than plates, the cells that receive a seed are picked randomly.
+pub struct Jittered;
+
+impl SeedStrategy for Jittered {
+    fn place(&self, options: &GenerateOptions, _mesh: &Mesh) -> Seeds {
+        let count = options.plates.count;
+        let mut rng = Pcg64Mcg::seed_from_u64(options.seed);
+        let (width, height) = (options.width, options.height);
+
+        // Pick the grid that best matches the map aspect ratio
+        let aspect = width as f32 / height as f32;
+        let mut columns = ((count as f32 * aspect).sqrt().round() as u32).clamp(1, width);
+        let rows = count.div_ceil(columns).min(height);
+        columns = count.div_ceil(rows).min(width);
+
+        let mut cells: Vec<(u32, u32)> = (0..columns)
+            .flat_map(|column| (0..rows).map(move |row| (column, row)))
+            .collect();
+        cells.shuffle(&mut rng);
+
+        cells
+            .into_iter()
+            .take(count as usize)
+            .map(|(column, row)| {
+                let (x_start, x_end) = (column * width / columns, (column + 1) * width / columns);
+                let (y_start, y_end) = (row * height / rows, (row + 1) * height / rows);
+                Coord::new(
+                    rng.gen_range(x_start..x_end) as i32,
+                    rng.gen_range(y_start..y_end) as i32,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Uses a user supplied list of seeds as is.
+pub struct List<'a> {
+    pub seeds: &'a [Coord],
+}
+
+impl SeedStrategy for List<'_> {
+    fn place(&self, _options: &GenerateOptions, _mesh: &Mesh) -> Seeds {
+        self.seeds.to_vec()
+    }
+}
+
+/// Reads a list of seeds from a file.
+///
+/// The file contains one `x,y` offset coordinate per line. Empty lines and lines starting with
+/// `#` are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::mesh::Coord;
+/// use hextergen::topography::seeds;
+///
+/// let path = std::env::temp_dir().join("hextergen_seeds_doctest.txt");
+/// std::fs::write(&path, "# plates\n10,20\n 30 , 5\n").unwrap();
+///
+/// let seeds = seeds::read(&path).unwrap();
+/// assert_eq!(seeds, vec![Coord::new(10, 20), Coord::new(30, 5)]);
+/// ```
+pub fn read(path: &Path) -> std::io::Result<Seeds> {
+    let content = std::fs::read_to_string(path)?;
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            let invalid = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid seed on line {}: {}", number, line),
+                )
+            };
+            let (x, y) = line.split_once(',').ok_or_else(invalid)?;
+            let x = x.trim().parse::<i32>().map_err(|_| invalid())?;
+            let y = y.trim().parse::<i32>().map_err(|_| invalid())?;
+            Ok(Coord::new(x, y))
+        })
+        .collect()
+}
@@ -1,21 +1,62 @@
+use noise::core::worley::{distance_functions::euclidean, worley_2d, worley_3d, ReturnType};
+use noise::permutationtable::PermutationTable;
 use noise::{NoiseFn, OpenSimplex};
 
+/// A noise generator sampled in two or three dimensions.
+///
+/// Every implementation returns values between -1 and 1, so the noise families can be swapped
+/// in any generation stage.
+pub trait Noise: Send + Sync {
+    /// Gets the noise value at the given 2D coordinates.
+    fn d2(&self, x: f32, y: f32) -> f32;
+
+    /// Gets the noise value at the given 3D coordinates.
+    fn d3(&self, point: (f32, f32, f32)) -> f32;
+}
+
+// Shared octave parameters of the fractal noises
+struct Octaves {
+    frequency: f32,
+    amplitude: f32,
+    octaves: u32,
+    persistence: f32,
+    lacunarity: f32,
+}
+
+impl Octaves {
+    // Sums the octaves returned by `sample` for each frequency, normalized by the total amplitude
+    fn sum(&self, mut sample: impl FnMut(f64) -> f32) -> f32 {
+        let mut total = 0.0;
+        let mut max_value = 0.0;
+        let mut amplitude = self.amplitude;
+        let mut frequency = self.frequency;
+
+        for _ in 0..self.octaves {
+            total += sample(frequency as f64) * amplitude;
+            max_value += amplitude;
+            amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+
+        total / max_value
+    }
+}
+
 /// The `OctaveNoise` struct represents a noise generator that uses the OpenSimplex noise function.
 /// It allows you to generate noise with multiple octaves, which can create more complex and natural-looking noise patterns.
 ///
 /// # Fields
 ///
 /// * `noise_fn`: An instance of the OpenSimplex noise function.
-/// * `frequency`: The frequency of the first octave of noise.
-/// * `amplitude`: The amplitude of the first octave of noise.
-/// * `octaves`: The number of octaves of noise to generate.
-/// * `persistence`: The persistence of the noise. This controls how much each octave contributes to the final noise. It is typically set between 0 and 1, with higher values giving more contribution to the later octaves.
+/// * `octaves`: The octave parameters:
+///   * `frequency`: The frequency of the first octave of noise.
+///   * `amplitude`: The amplitude of the first octave of noise.
+///   * `octaves`: The number of octaves of noise to generate.
+///   * `persistence`: The persistence of the noise. This controls how much each octave contributes to the final noise. It is typically set between 0 and 1, with higher values giving more contribution to the later octaves.
+///   * `lacunarity`: The frequency multiplier between octaves. It is typically set to 2, doubling the frequency on each octave.
 pub struct OctaveNoise {
     noise_fn: OpenSimplex,
-    frequency: f32,
-    amplitude: f32,
-    octaves: u32,
-    persistence: f32,
+    octaves: Octaves,
 }
 
 impl OctaveNoise {
@@ -28,21 +69,34 @@ impl OctaveNoise {
     /// * `amplitude`: The amplitude of the first octave of noise.
     /// * `octaves`: The number of octaves of noise to generate.
     /// * `persistence`: The persistence of the noise.
+    /// * `lacunarity`: The frequency multiplier between octaves.
     ///
     /// # Returns
     ///
     /// A new `OctaveNoise` instance.
-    pub fn new(seed: u64, frequency: f32, amplitude: f32, octaves: u32, persistence: f32) -> Self {
+    pub fn new(
+        seed: u64,
+        frequency: f32,
+        amplitude: f32,
+        octaves: u32,
+        persistence: f32,
+        lacunarity: f32,
+    ) -> Self {
         let seed = seed as u32;
         OctaveNoise {
             noise_fn: OpenSimplex::new(seed),
-            frequency,
-            amplitude,
-            octaves,
-            persistence,
+            octaves: Octaves {
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            },
         }
     }
+}
 
+impl Noise for OctaveNoise {
     /// Gets the noise value at the given coordinates.
     ///
     /// # Arguments
@@ -53,43 +107,255 @@ impl OctaveNoise {
     /// # Returns
     ///
     /// The noise value at the given coordinates. This is a value between -1 and 1.
-    pub fn d2(&self, x: f32, y: f32) -> f32 {
-        let mut total = 0.0;
-        let mut max_value = 0.0;
-        let mut amplitude = self.amplitude;
-        let mut frequency = self.frequency;
+    fn d2(&self, x: f32, y: f32) -> f32 {
+        self.octaves.sum(|frequency| {
+            self.noise_fn
+                .get([x as f64 * frequency, y as f64 * frequency]) as f32
+        })
+    }
 
-        for _ in 0..self.octaves {
-            total += self
+    fn d3(&self, (x, y, z): (f32, f32, f32)) -> f32 {
+        self.octaves.sum(|frequency| {
+            self.noise_fn.get([
+                x as f64 * frequency,
+                y as f64 * frequency,
+                z as f64 * frequency,
+            ]) as f32
+        })
+    }
+}
+
+/// Ridged multifractal noise built on OpenSimplex.
+///
+/// Each octave folds the noise around zero, turning its zero crossings into sharp ridges, and is
+/// weighted by the previous octave so the detail concentrates on the ridges. It is suited for
+/// mountain ranges.
+pub struct RidgedNoise {
+    noise_fn: OpenSimplex,
+    octaves: Octaves,
+}
+
+impl RidgedNoise {
+    // How strongly each octave feeds the weight of the next one
+    const GAIN: f32 = 2.0;
+
+    /// Creates a new `RidgedNoise` instance, with the same arguments as [`OctaveNoise::new`].
+    pub fn new(
+        seed: u64,
+        frequency: f32,
+        amplitude: f32,
+        octaves: u32,
+        persistence: f32,
+        lacunarity: f32,
+    ) -> Self {
+        RidgedNoise {
+            noise_fn: OpenSimplex::new(seed as u32),
+            octaves: Octaves {
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            },
+        }
+    }
+
+    // Sums the ridged octaves, returning a value between -1 and 1
+    fn ridged(&self, sample: impl Fn(f64) -> f64) -> f32 {
+        let mut weight = 1.0;
+        let ridged = self.octaves.sum(|frequency| {
+            let signal = (1.0 - sample(frequency).abs() as f32).powi(2) * weight;
+            weight = (signal * Self::GAIN).clamp(0.0, 1.0);
+            signal
+        });
+
+        ridged * 2.0 - 1.0
+    }
+}
+
+impl Noise for RidgedNoise {
+    fn d2(&self, x: f32, y: f32) -> f32 {
+        self.ridged(|frequency| {
+            self.noise_fn
+                .get([x as f64 * frequency, y as f64 * frequency])
+        })
+    }
+
+    fn d3(&self, (x, y, z): (f32, f32, f32)) -> f32 {
+        self.ridged(|frequency| {
+            self.noise_fn.get([
+                x as f64 * frequency,
+                y as f64 * frequency,
+                z as f64 * frequency,
+            ])
+        })
+    }
+}
+
+/// Billow noise built on OpenSimplex.
+///
+/// Each octave uses the absolute value of the noise, producing rounded lumps separated by creases.
+/// It is suited for hills and dunes.
+pub struct BillowNoise {
+    noise_fn: OpenSimplex,
+    octaves: Octaves,
+}
+
+impl BillowNoise {
+    /// Creates a new `BillowNoise` instance, with the same arguments as [`OctaveNoise::new`].
+    pub fn new(
+        seed: u64,
+        frequency: f32,
+        amplitude: f32,
+        octaves: u32,
+        persistence: f32,
+        lacunarity: f32,
+    ) -> Self {
+        BillowNoise {
+            noise_fn: OpenSimplex::new(seed as u32),
+            octaves: Octaves {
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            },
+        }
+    }
+}
+
+impl Noise for BillowNoise {
+    fn d2(&self, x: f32, y: f32) -> f32 {
+        self.octaves.sum(|frequency| {
+            let value = self
                 .noise_fn
-                .get([(x * frequency) as f64, (y * frequency) as f64]) as f32
-                * amplitude;
-            max_value += amplitude;
-            amplitude *= self.persistence;
-            frequency *= 2.0;
+                .get([x as f64 * frequency, y as f64 * frequency]);
+            value.abs() as f32 * 2.0 - 1.0
+        })
+    }
+
+    fn d3(&self, (x, y, z): (f32, f32, f32)) -> f32 {
+        self.octaves.sum(|frequency| {
+            let value = self.noise_fn.get([
+                x as f64 * frequency,
+                y as f64 * frequency,
+                z as f64 * frequency,
+            ]);
+            value.abs() as f32 * 2.0 - 1.0
+        })
+    }
+}
+
+/// Fractal Worley (cellular) noise.
+///
+/// Each octave returns the inverted distance to the closest feature point, so every cell is a
+/// dome whose borders form a network of valleys. It is suited for cellular terrain.
+pub struct WorleyNoise {
+    hasher: PermutationTable,
+    octaves: Octaves,
+}
+
+impl WorleyNoise {
+    /// Creates a new `WorleyNoise` instance, with the same arguments as [`OctaveNoise::new`].
+    pub fn new(
+        seed: u64,
+        frequency: f32,
+        amplitude: f32,
+        octaves: u32,
+        persistence: f32,
+        lacunarity: f32,
+    ) -> Self {
+        WorleyNoise {
+            hasher: PermutationTable::new(seed as u32),
+            octaves: Octaves {
+                frequency,
+                amplitude,
+                octaves,
+                persistence,
+                lacunarity,
+            },
         }
+    }
+}
 
-        total / max_value
+impl Noise for WorleyNoise {
+    fn d2(&self, x: f32, y: f32) -> f32 {
+        self.octaves.sum(|frequency| {
+            let point = [x as f64 * frequency, y as f64 * frequency];
+            let distance = worley_2d(&self.hasher, euclidean, ReturnType::Distance, point);
+            (-distance).clamp(-1.0, 1.0) as f32
+        })
     }
 
-    pub fn d3(&self, (x, y, z): (f32, f32, f32)) -> f32 {
-        let mut total = 0.0;
-        let mut max_value = 0.0;
-        let mut amplitude = self.amplitude;
-        let mut frequency = self.frequency;
+    fn d3(&self, (x, y, z): (f32, f32, f32)) -> f32 {
+        self.octaves.sum(|frequency| {
+            let point = [
+                x as f64 * frequency,
+                y as f64 * frequency,
+                z as f64 * frequency,
+            ];
+            let distance = worley_3d(&self.hasher, euclidean, ReturnType::Distance, point);
+            (-distance).clamp(-1.0, 1.0) as f32
+        })
+    }
+}
 
-        for _ in 0..self.octaves {
-            total += self.noise_fn.get([
-                (x * frequency) as f64,
-                (y * frequency) as f64,
-                (z * frequency) as f64,
-            ]) as f32
-                * amplitude;
-            max_value += amplitude;
-            amplitude *= self.persistence;
-            frequency *= 2.0;
+/// Domain warped noise.
+///
+/// The coordinates are displaced by a second noise before sampling the source noise, bending its
+/// features into swirls and folds.
+///
+/// # Examples
+///
+/// ```
+/// use hextergen::utils::noise::{Noise, OctaveNoise, RidgedNoise, WarpedNoise};
+///
+/// let source = RidgedNoise::new(42, 5.0, 1.0, 8, 0.5, 2.0);
+/// let warp = OctaveNoise::new(43, 2.0, 1.0, 4, 0.5, 2.0);
+/// let noise = WarpedNoise::new(Box::new(source), Box::new(warp), 0.1);
+///
+/// let value = noise.d3((0.1, 0.2, 0.3));
+/// assert!((-1.0..=1.0).contains(&value));
+/// ```
+pub struct WarpedNoise {
+    source: Box<dyn Noise>,
+    warp: Box<dyn Noise>,
+    strength: f32,
+}
+
+impl WarpedNoise {
+    // Offsets sampling the warp noise on each axis, so the axes are not displaced equally
+    const OFFSETS: [f32; 3] = [0.0, 5.2, 1.7];
+
+    /// Creates a new `WarpedNoise` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `source`: The noise sampled at the warped coordinates.
+    /// * `warp`: The noise displacing the coordinates.
+    /// * `strength`: The maximum displacement of the coordinates.
+    pub fn new(source: Box<dyn Noise>, warp: Box<dyn Noise>, strength: f32) -> Self {
+        WarpedNoise {
+            source,
+            warp,
+            strength,
         }
+    }
+}
 
-        total / max_value
+impl Noise for WarpedNoise {
+    fn d2(&self, x: f32, y: f32) -> f32 {
+        let [ox, oy, _] = Self::OFFSETS;
+        let dx = self.warp.d2(x + ox, y + ox) * self.strength;
+        let dy = self.warp.d2(x + oy, y + oy) * self.strength;
+        self.source.d2(x + dx, y + dy)
+    }
+
+    fn d3(&self, (x, y, z): (f32, f32, f32)) -> f32 {
+        let [ox, oy, oz] = Self::OFFSETS;
+        let dx = self.warp.d3((x + ox, y + ox, z + ox)) * self.strength;
+        let dy = self.warp.d3((x + oy, y + oy, z + oy)) * self.strength;
+        let dz = self.warp.d3((x + oz, y + oz, z + oz)) * self.strength;
+        self.source.d3((x + dx, y + dy, z + dz))
     }
 }